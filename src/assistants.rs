@@ -0,0 +1,438 @@
+use std::f64::consts::TAU;
+
+use crate::vector::{Ellipse, Vector2};
+
+/// Distance the pointer has to travel before a stroke picks its guide.
+const DECIDE_DISTANCE: f64 = 8.0;
+
+/// Distance from a guide that costs as much as a right angle to its direction.
+const SNAP_DISTANCE: f64 = 60.0;
+
+/// Segments of an ellipse drawn in the overlay.
+const ELLIPSE_SEGMENTS: usize = 96;
+
+/// Rays drawn from every vanishing point in the overlay.
+const PERSPECTIVE_RAYS: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AssistantKind {
+    Ruler,
+    Parallel,
+    Ellipse,
+    ConcentricEllipse,
+    /// Perspective with the given number of vanishing points, from one to three.
+    Perspective(usize),
+}
+
+impl AssistantKind {
+    pub const ALL: [AssistantKind; 7] = [
+        AssistantKind::Ruler,
+        AssistantKind::Parallel,
+        AssistantKind::Ellipse,
+        AssistantKind::ConcentricEllipse,
+        AssistantKind::Perspective(1),
+        AssistantKind::Perspective(2),
+        AssistantKind::Perspective(3),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AssistantKind::Ruler => "Ruler",
+            AssistantKind::Parallel => "Parallel lines",
+            AssistantKind::Ellipse => "Ellipse",
+            AssistantKind::ConcentricEllipse => "Concentric ellipses",
+            AssistantKind::Perspective(1) => "1-point perspective",
+            AssistantKind::Perspective(2) => "2-point perspective",
+            AssistantKind::Perspective(_) => "3-point perspective",
+        }
+    }
+}
+
+/// A guide strokes are bent onto.
+///
+/// The meaning of the handles depends on the kind: the ends of the line for rulers
+/// and parallel lines; the center, the end of the major axis and the end of the
+/// minor axis for ellipses; the vanishing points for perspective.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assistant {
+    pub kind: AssistantKind,
+    pub enabled: bool,
+    pub handles: Vec<Vector2>,
+}
+
+impl Assistant {
+    /// Assistant with handles placed around `center`, `extent` apart.
+    pub fn new(kind: AssistantKind, center: Vector2, extent: f64) -> Self {
+        let handles = match kind {
+            AssistantKind::Ruler | AssistantKind::Parallel => vec![
+                center - Vector2::new(extent, 0.0),
+                center + Vector2::new(extent, 0.0),
+            ],
+            AssistantKind::Ellipse | AssistantKind::ConcentricEllipse => vec![
+                center,
+                center + Vector2::new(extent, 0.0),
+                center + Vector2::new(0.0, extent / 2.0),
+            ],
+            AssistantKind::Perspective(1) => vec![center],
+            AssistantKind::Perspective(2) => vec![
+                center - Vector2::new(extent * 2.0, 0.0),
+                center + Vector2::new(extent * 2.0, 0.0),
+            ],
+            AssistantKind::Perspective(_) => vec![
+                center - Vector2::new(extent * 2.0, 0.0),
+                center + Vector2::new(extent * 2.0, 0.0),
+                center + Vector2::new(0.0, extent * 3.0),
+            ],
+        };
+        Self {
+            kind,
+            enabled: true,
+            handles,
+        }
+    }
+
+    /// Guides a stroke starting at `start` can follow.
+    fn guides_at(&self, start: Vector2) -> Vec<Guide> {
+        match self.kind {
+            AssistantKind::Ruler => vec![Guide::Line {
+                origin: self.handles[0],
+                direction: self.handles[1] - self.handles[0],
+            }],
+            AssistantKind::Parallel => vec![Guide::Line {
+                origin: start,
+                direction: self.handles[1] - self.handles[0],
+            }],
+            AssistantKind::Ellipse => vec![Guide::Ellipse(self.ellipse())],
+            AssistantKind::ConcentricEllipse => {
+                let ellipse = self.ellipse();
+                let k = ellipse.to_unit_circle(start).len();
+                vec![Guide::Ellipse(Ellipse::new(
+                    ellipse.center,
+                    ellipse.radii * k,
+                    ellipse.angle,
+                ))]
+            }
+            AssistantKind::Perspective(_) => self
+                .handles
+                .iter()
+                .map(|&vanishing| Guide::Line {
+                    origin: start,
+                    direction: vanishing - start,
+                })
+                .collect(),
+        }
+    }
+
+    fn ellipse(&self) -> Ellipse {
+        let center = self.handles[0];
+        let major = self.handles[1] - center;
+        let minor = (self.handles[2] - center).len();
+        Ellipse::new(
+            center,
+            Vector2::new(major.len(), minor),
+            major.y.atan2(major.x),
+        )
+    }
+
+    /// Polylines of the on-canvas overlay, long lines are `length` long.
+    pub fn overlay(&self, length: f64) -> Vec<Vec<Vector2>> {
+        match self.kind {
+            AssistantKind::Ruler => vec![self.handles.clone()],
+            AssistantKind::Parallel => {
+                let (a, b) = (self.handles[0], self.handles[1]);
+                let direction = (b - a).norm();
+                vec![vec![a - direction * length, b + direction * length]]
+            }
+            AssistantKind::Ellipse => vec![self.ellipse().polyline(ELLIPSE_SEGMENTS)],
+            AssistantKind::ConcentricEllipse => {
+                let ellipse = self.ellipse();
+                [0.5, 1.0, 1.5]
+                    .iter()
+                    .map(|&k| {
+                        Ellipse::new(ellipse.center, ellipse.radii * k, ellipse.angle)
+                            .polyline(ELLIPSE_SEGMENTS)
+                    })
+                    .collect()
+            }
+            AssistantKind::Perspective(_) => {
+                let mut lines: Vec<Vec<Vector2>> = self
+                    .handles
+                    .iter()
+                    .flat_map(|&vanishing| {
+                        (0..PERSPECTIVE_RAYS).map(move |i| {
+                            let angle = TAU * i as f64 / PERSPECTIVE_RAYS as f64;
+                            let ray = Vector2::new(angle.cos(), angle.sin()) * length;
+                            vec![vanishing, vanishing + ray]
+                        })
+                    })
+                    .collect();
+                if self.handles.len() >= 2 {
+                    let (a, b) = (self.handles[0], self.handles[1]);
+                    let direction = (b - a).norm();
+                    lines.push(vec![a - direction * length, b + direction * length]);
+                }
+                lines
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Guide {
+    Line { origin: Vector2, direction: Vector2 },
+    Ellipse(Ellipse),
+}
+
+impl Guide {
+    fn project(&self, point: Vector2) -> Vector2 {
+        match *self {
+            Guide::Line { origin, direction } => {
+                if direction.len() == 0.0 {
+                    return origin;
+                }
+                let direction = direction.norm();
+                origin + direction * (point - origin).dot(direction)
+            }
+            Guide::Ellipse(ellipse) => {
+                let local = ellipse.to_unit_circle(point);
+                let local = if local.len() == 0.0 {
+                    Vector2::new(1.0, 0.0)
+                } else {
+                    local.norm()
+                };
+                ellipse.from_unit_circle(local)
+            }
+        }
+    }
+
+    /// Unit direction of the guide at its point closest to `point`.
+    fn tangent(&self, point: Vector2) -> Vector2 {
+        match *self {
+            Guide::Line { direction, .. } => direction.norm(),
+            Guide::Ellipse(ellipse) => {
+                let local = ellipse.to_unit_circle(point);
+                let t = local.y.atan2(local.x);
+                let tangent = Vector2::new(-t.sin() * ellipse.radii.x, t.cos() * ellipse.radii.y);
+                tangent.rotate(ellipse.angle).norm()
+            }
+        }
+    }
+
+    /// How badly a stroke starting at `start` in `direction` matches the guide.
+    fn cost(&self, start: Vector2, direction: Vector2) -> f64 {
+        let tangent = self.tangent(start);
+        if tangent.x.is_nan() || tangent.y.is_nan() {
+            return f64::INFINITY;
+        }
+        // Guides are undirected, a stroke can follow either way
+        let angle = tangent.dot(direction).abs().min(1.0).acos();
+        let distance = (self.project(start) - start).len();
+        angle / std::f64::consts::FRAC_PI_2 + distance / SNAP_DISTANCE
+    }
+}
+
+/// Where an assisted stroke goes after a pointer move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Assisted {
+    /// The guide isn't picked yet, nothing to paint.
+    Pending,
+    /// The guide was just picked, paint from the first point to the second.
+    Start(Vector2, Vector2),
+    To(Vector2),
+}
+
+/// Stroke bent onto the guide that best matches its start.
+pub struct AssistedStroke {
+    start: Vector2,
+    /// `None` until picked, then the guide or `None` for a free stroke.
+    guide: Option<Option<Guide>>,
+}
+
+impl AssistedStroke {
+    pub fn new(start: Vector2) -> Self {
+        Self { start, guide: None }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.guide.is_none()
+    }
+
+    pub fn to(&mut self, assistants: &[Assistant], point: Vector2) -> Assisted {
+        match self.guide {
+            Some(Some(guide)) => return Assisted::To(guide.project(point)),
+            Some(None) => return Assisted::To(point),
+            None => {}
+        }
+
+        let delta = point - self.start;
+        if delta.len() < DECIDE_DISTANCE {
+            return Assisted::Pending;
+        }
+        let direction = delta.norm();
+        let start = self.start;
+        let guide = assistants
+            .iter()
+            .filter(|assistant| assistant.enabled)
+            .flat_map(|assistant| assistant.guides_at(start))
+            .map(|guide| (guide.cost(start, direction), guide))
+            .filter(|(cost, _)| cost.is_finite())
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, guide)| guide);
+
+        self.guide = Some(guide);
+        match guide {
+            Some(guide) => Assisted::Start(guide.project(start), guide.project(point)),
+            None => Assisted::Start(start, point),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vector2, expected: Vector2) {
+        assert!(
+            (actual - expected).len() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn ruler(from: Vector2, to: Vector2) -> Assistant {
+        Assistant {
+            kind: AssistantKind::Ruler,
+            enabled: true,
+            handles: vec![from, to],
+        }
+    }
+
+    #[test]
+    fn projects_onto_lines() {
+        let line = Guide::Line {
+            origin: Vector2::new(1.0, 1.0),
+            direction: Vector2::new(10.0, 10.0),
+        };
+        assert_close(line.project(Vector2::new(4.0, 2.0)), Vector2::new(3.0, 3.0));
+        assert_close(
+            line.project(Vector2::new(-3.0, -1.0)),
+            Vector2::new(-2.0, -2.0),
+        );
+
+        let degenerate = Guide::Line {
+            origin: Vector2::new(1.0, 1.0),
+            direction: Vector2::new(0.0, 0.0),
+        };
+        assert_close(
+            degenerate.project(Vector2::new(5.0, 5.0)),
+            Vector2::new(1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn projects_onto_ellipses() {
+        let center = Vector2::new(10.0, 20.0);
+        let ellipse = Guide::Ellipse(Ellipse::new(center, Vector2::new(4.0, 2.0), 0.0));
+        assert_close(
+            ellipse.project(center + Vector2::new(8.0, 0.0)),
+            center + Vector2::new(4.0, 0.0),
+        );
+        assert_close(
+            ellipse.project(center + Vector2::new(0.0, -0.5)),
+            center + Vector2::new(0.0, -2.0),
+        );
+
+        // The major axis turned to point down
+        let turned = Guide::Ellipse(Ellipse::new(
+            center,
+            Vector2::new(4.0, 2.0),
+            std::f64::consts::FRAC_PI_2,
+        ));
+        assert_close(
+            turned.project(center + Vector2::new(0.0, 10.0)),
+            center + Vector2::new(0.0, 4.0),
+        );
+        assert_close(
+            turned.project(center + Vector2::new(-7.0, 0.0)),
+            center + Vector2::new(-2.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn waits_before_picking_a_guide() {
+        let assistants = [ruler(Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0))];
+        let start = Vector2::new(10.0, 3.0);
+        let mut stroke = AssistedStroke::new(start);
+
+        let near = start + Vector2::new(DECIDE_DISTANCE - 1.0, 0.0);
+        assert_eq!(stroke.to(&assistants, near), Assisted::Pending);
+        assert!(stroke.is_pending());
+
+        let far = start + Vector2::new(DECIDE_DISTANCE + 1.0, 0.0);
+        assert_eq!(
+            stroke.to(&assistants, far),
+            Assisted::Start(Vector2::new(10.0, 0.0), Vector2::new(far.x, 0.0))
+        );
+        assert!(!stroke.is_pending());
+        assert_eq!(
+            stroke.to(&assistants, Vector2::new(50.0, -7.0)),
+            Assisted::To(Vector2::new(50.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn picks_the_guide_along_the_stroke() {
+        let origin = Vector2::new(0.0, 0.0);
+        let assistants = [
+            ruler(origin, Vector2::new(100.0, 0.0)),
+            ruler(origin, Vector2::new(0.0, 100.0)),
+        ];
+        let start = Vector2::new(2.0, 2.0);
+
+        let mut horizontal = AssistedStroke::new(start);
+        horizontal.to(&assistants, start + Vector2::new(20.0, 3.0));
+        assert_eq!(
+            horizontal.to(&assistants, Vector2::new(40.0, 9.0)),
+            Assisted::To(Vector2::new(40.0, 0.0))
+        );
+
+        let mut vertical = AssistedStroke::new(start);
+        vertical.to(&assistants, start + Vector2::new(-3.0, 20.0));
+        assert_eq!(
+            vertical.to(&assistants, Vector2::new(9.0, 40.0)),
+            Assisted::To(Vector2::new(0.0, 40.0))
+        );
+    }
+
+    #[test]
+    fn follows_the_matching_vanishing_point() {
+        let assistants = [Assistant {
+            kind: AssistantKind::Perspective(2),
+            enabled: true,
+            handles: vec![Vector2::new(-100.0, 0.0), Vector2::new(100.0, 0.0)],
+        }];
+        let start = Vector2::new(0.0, 50.0);
+        let mut stroke = AssistedStroke::new(start);
+        // Heading towards the right vanishing point
+        stroke.to(&assistants, start + Vector2::new(10.0, -4.0));
+        match stroke.to(&assistants, Vector2::new(50.0, 0.0)) {
+            Assisted::To(point) => assert_close(point, Vector2::new(60.0, 20.0)),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn strokes_stay_free_without_enabled_assistants() {
+        let mut disabled = ruler(Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0));
+        disabled.enabled = false;
+        let start = Vector2::new(0.0, 5.0);
+        let point = Vector2::new(3.0, 20.0);
+        let mut stroke = AssistedStroke::new(start);
+        assert_eq!(stroke.to(&[disabled], point), Assisted::Start(start, point));
+        assert_eq!(
+            stroke.to(&[], Vector2::new(7.0, 7.0)),
+            Assisted::To(Vector2::new(7.0, 7.0))
+        );
+    }
+}
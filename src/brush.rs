@@ -0,0 +1,450 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::{Color, Palette},
+    grain::Grain,
+    vector::{Ellipse, Vector2},
+};
+
+/// Per-dab randomization. Every jitter is in `0.0..=1.0`, zero disables it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Dynamics {
+    /// Maximum distance of a dab from the stroke, relative to the brush size.
+    pub scatter: f64,
+    pub size_jitter: f64,
+    pub angle_jitter: f64,
+    pub roundness_jitter: f64,
+    /// Dabs placed at every spacing step.
+    pub count: u32,
+    pub hue_jitter: f64,
+    pub saturation_jitter: f64,
+    pub brightness_jitter: f64,
+    /// How far a dab color may drift from `Palette::main` towards `Palette::help`.
+    pub foreground_background: f64,
+}
+
+impl Default for Dynamics {
+    fn default() -> Self {
+        Self {
+            scatter: 0.0,
+            size_jitter: 0.0,
+            angle_jitter: 0.0,
+            roundness_jitter: 0.0,
+            count: 1,
+            hue_jitter: 0.0,
+            saturation_jitter: 0.0,
+            brightness_jitter: 0.0,
+            foreground_background: 0.0,
+        }
+    }
+}
+
+/// How dabs are composited with the layer pixels, independent of the layer blend mode.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum PaintMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    /// Paints only under the existing pixels.
+    Behind,
+    /// Erases with the dab coverage.
+    Clear,
+    Darken,
+    Lighten,
+    Color,
+    /// Keeps the layer transparency, paints only over existing pixels.
+    LockAlpha,
+}
+
+impl PaintMode {
+    pub const ALL: [PaintMode; 9] = [
+        PaintMode::Normal,
+        PaintMode::Multiply,
+        PaintMode::Screen,
+        PaintMode::Behind,
+        PaintMode::Clear,
+        PaintMode::Darken,
+        PaintMode::Lighten,
+        PaintMode::Color,
+        PaintMode::LockAlpha,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaintMode::Normal => "Normal",
+            PaintMode::Multiply => "Multiply",
+            PaintMode::Screen => "Screen",
+            PaintMode::Behind => "Behind",
+            PaintMode::Clear => "Clear",
+            PaintMode::Darken => "Darken",
+            PaintMode::Lighten => "Lighten",
+            PaintMode::Color => "Color",
+            PaintMode::LockAlpha => "Lock alpha",
+        }
+    }
+
+    /// Value of `globalCompositeOperation` painting in this mode.
+    pub fn composite_operation(self) -> &'static str {
+        match self {
+            PaintMode::Normal => "source-over",
+            PaintMode::Multiply => "multiply",
+            PaintMode::Screen => "screen",
+            PaintMode::Behind => "destination-over",
+            PaintMode::Clear => "destination-out",
+            PaintMode::Darken => "darken",
+            PaintMode::Lighten => "lighten",
+            PaintMode::Color => "color",
+            PaintMode::LockAlpha => "source-atop",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Brush {
+    /// Diameter in pixels.
+    pub size: f64,
+    /// Distance between dabs, relative to the brush size.
+    pub spacing: f64,
+    /// Tip rotation in radians.
+    pub angle: f64,
+    /// Ratio of the tip's minor axis to its major axis.
+    pub roundness: f64,
+    /// Part of the radius painted at full opacity, the rest fades out.
+    pub hardness: f64,
+    pub opacity: f64,
+    /// Id of a custom tip in `Resources`, the tip is round when `None`.
+    pub tip: Option<u32>,
+    /// Id of a pattern in `Resources` to paint with instead of the palette color.
+    pub pattern: Option<u32>,
+    pub grain: Option<Grain>,
+    pub mode: PaintMode,
+    pub dynamics: Dynamics,
+}
+
+impl Brush {
+    pub fn spacing_px(&self) -> f64 {
+        (self.spacing * self.size).max(1.0)
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            size: 50.0,
+            spacing: 0.1,
+            angle: 0.0,
+            roundness: 1.0,
+            hardness: 1.0,
+            opacity: 1.0,
+            tip: None,
+            pattern: None,
+            grain: None,
+            mode: PaintMode::Normal,
+            dynamics: Dynamics::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Dab {
+    pub shape: Ellipse,
+    pub hardness: f64,
+    pub color: Color,
+}
+
+/// Places dabs along a stroke keeping the brush spacing between pointer events.
+pub struct BrushStroke {
+    brush: Brush,
+    rng: StdRng,
+    last: Option<Vector2>,
+    distance_to_next: f64,
+}
+
+impl BrushStroke {
+    pub fn new(brush: Brush, seed: u64) -> Self {
+        Self {
+            brush,
+            rng: StdRng::seed_from_u64(seed),
+            last: None,
+            distance_to_next: 0.0,
+        }
+    }
+
+    /// Continues the stroke from `point` without placing a dab there.
+    pub fn move_to(&mut self, point: Vector2) {
+        self.last = Some(point);
+        self.distance_to_next = self.brush.spacing_px();
+    }
+
+    pub fn stroke_to(&mut self, point: Vector2, palette: &Palette) -> Vec<Dab> {
+        let spacing = self.brush.spacing_px();
+        let mut positions = vec![];
+
+        match self.last {
+            None => {
+                positions.push(point);
+                self.distance_to_next = spacing;
+            }
+            Some(last) => {
+                let delta = point - last;
+                let len = delta.len();
+                if len > 0.0 {
+                    let mut t = self.distance_to_next;
+                    while t <= len {
+                        positions.push(last + delta * (t / len));
+                        t += spacing;
+                    }
+                    self.distance_to_next = t - len;
+                }
+            }
+        }
+        self.last = Some(point);
+
+        let mut dabs = vec![];
+        for position in positions {
+            for _ in 0..self.brush.dynamics.count.max(1) {
+                dabs.push(self.dab_at(position, palette));
+            }
+        }
+        dabs
+    }
+
+    fn dab_at(&mut self, position: Vector2, palette: &Palette) -> Dab {
+        let brush = self.brush;
+        let dynamics = brush.dynamics;
+
+        let scatter = dynamics.scatter * brush.size * self.rng.gen::<f64>().sqrt();
+        let scatter_angle = self.rng.gen_range(0.0, std::f64::consts::TAU);
+        let center = position + Vector2::new(scatter_angle.cos(), scatter_angle.sin()) * scatter;
+
+        let size = brush.size * (1.0 - dynamics.size_jitter * self.rng.gen::<f64>());
+        let angle = brush.angle + dynamics.angle_jitter * self.signed() * std::f64::consts::PI;
+        let roundness =
+            (brush.roundness * (1.0 - dynamics.roundness_jitter * self.rng.gen::<f64>())).max(0.05);
+
+        Dab {
+            shape: Ellipse::new(
+                center,
+                Vector2::new(size / 2.0, size * roundness / 2.0),
+                angle,
+            ),
+            hardness: brush.hardness,
+            color: self.dab_color(palette),
+        }
+    }
+
+    fn dab_color(&mut self, palette: &Palette) -> Color {
+        let dynamics = self.brush.dynamics;
+
+        let base = palette.main.lerp(
+            palette.help,
+            dynamics.foreground_background * self.rng.gen::<f64>(),
+        );
+        let base = base.with_alpha((base.get_a() as f64 * self.brush.opacity) as u8);
+
+        if dynamics.hue_jitter == 0.0
+            && dynamics.saturation_jitter == 0.0
+            && dynamics.brightness_jitter == 0.0
+        {
+            return base;
+        }
+
+        let (h, s, v) = base.to_hsv();
+        let h = (h + dynamics.hue_jitter * self.signed() * std::f64::consts::PI)
+            .rem_euclid(std::f64::consts::TAU);
+        let s = (s + dynamics.saturation_jitter * self.signed()).clamp(0.0, 1.0);
+        let v = (v + dynamics.brightness_jitter * self.signed()).clamp(0.0, 1.0);
+
+        Color::from_hsv(h, s, v).with_alpha(base.get_a())
+    }
+
+    fn signed(&mut self) -> f64 {
+        self.rng.gen_range(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn palette() -> Palette {
+        Palette::new(Color::BLACK, Color::WHITE)
+    }
+
+    fn line(brush: Brush, length: f64) -> Vec<Dab> {
+        let mut stroke = BrushStroke::new(brush, 7);
+        let mut dabs = stroke.stroke_to(Vector2::new(0.0, 0.0), &palette());
+        dabs.extend(stroke.stroke_to(Vector2::new(length, 0.0), &palette()));
+        dabs
+    }
+
+    #[test]
+    fn dabs_are_spaced_along_the_stroke() {
+        let brush = Brush {
+            size: 50.0,
+            spacing: 0.1,
+            ..Brush::default()
+        };
+        let mut stroke = BrushStroke::new(brush, 7);
+        let mut xs = vec![];
+        for x in [0.0, 100.0, 102.0, 107.0] {
+            xs.extend(
+                stroke
+                    .stroke_to(Vector2::new(x, 0.0), &palette())
+                    .iter()
+                    .map(|dab| dab.shape.center.x),
+            );
+        }
+        let expected: Vec<f64> = (0..=21).map(|i| i as f64 * 5.0).collect();
+        assert_eq!(xs.len(), expected.len());
+        for (x, expected) in xs.iter().zip(expected) {
+            assert!((x - expected).abs() < 1e-9, "{} != {}", x, expected);
+        }
+    }
+
+    #[test]
+    fn spacing_is_at_least_a_pixel() {
+        let brush = Brush {
+            size: 2.0,
+            spacing: 0.1,
+            ..Brush::default()
+        };
+        assert_eq!(brush.spacing_px(), 1.0);
+        assert_eq!(line(brush, 10.0).len(), 11);
+    }
+
+    #[test]
+    fn count_places_dabs_per_step() {
+        let brush = Brush {
+            size: 50.0,
+            spacing: 0.1,
+            dynamics: Dynamics {
+                count: 3,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        assert_eq!(line(brush, 100.0).len(), 3 * 21);
+    }
+
+    #[test]
+    fn same_seed_places_same_dabs() {
+        let brush = Brush {
+            dynamics: Dynamics {
+                scatter: 1.0,
+                size_jitter: 0.5,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        let a = line(brush, 100.0);
+        let b = line(brush, 100.0);
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.shape, b.shape);
+        }
+    }
+
+    #[test]
+    fn shape_jitter_stays_in_bounds() {
+        let brush = Brush {
+            size: 40.0,
+            spacing: 0.1,
+            angle: 0.3,
+            roundness: 0.8,
+            dynamics: Dynamics {
+                scatter: 0.5,
+                size_jitter: 0.5,
+                angle_jitter: 0.5,
+                roundness_jitter: 0.5,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        let dabs = line(brush, 400.0);
+        for (i, dab) in dabs.iter().enumerate() {
+            let step = Vector2::new(i as f64 * 4.0, 0.0);
+            assert!((dab.shape.center - step).len() <= 20.0 + 1e-9);
+
+            let size = dab.shape.radii.x * 2.0;
+            assert!((20.0..=40.0).contains(&size), "size {}", size);
+            let roundness = dab.shape.radii.y / dab.shape.radii.x;
+            assert!((0.4 - 1e-9..=0.8 + 1e-9).contains(&roundness));
+            assert!((dab.shape.angle - 0.3).abs() <= PI / 2.0);
+        }
+        // The jitter does vary the dabs
+        assert!(dabs.iter().any(|dab| dab.shape.radii.x < 19.0));
+    }
+
+    #[test]
+    fn roundness_has_a_minimum() {
+        let brush = Brush {
+            roundness: 0.1,
+            dynamics: Dynamics {
+                roundness_jitter: 1.0,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        for dab in line(brush, 100.0) {
+            assert!(dab.shape.radii.y / dab.shape.radii.x >= 0.05 - 1e-9);
+        }
+    }
+
+    #[test]
+    fn foreground_background_mixes_towards_help() {
+        let brush = Brush {
+            opacity: 0.5,
+            dynamics: Dynamics {
+                foreground_background: 0.5,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        let dabs = line(brush, 100.0);
+        for dab in &dabs {
+            let color = dab.color;
+            assert!(color.get_r() <= 128);
+            assert_eq!(color.get_r(), color.get_g());
+            assert_eq!(color.get_r(), color.get_b());
+            assert_eq!(color.get_a(), 127);
+        }
+        assert!(dabs.iter().any(|dab| dab.color.get_r() > 0));
+    }
+
+    #[test]
+    fn color_jitter_stays_in_bounds() {
+        let brush = Brush {
+            dynamics: Dynamics {
+                hue_jitter: 0.1,
+                saturation_jitter: 0.2,
+                brightness_jitter: 0.2,
+                ..Dynamics::default()
+            },
+            ..Brush::default()
+        };
+        let base = Color::new(64, 128, 128, 255);
+        let (h0, s0, v0) = base.to_hsv();
+        let mut stroke = BrushStroke::new(brush, 7);
+        let palette = Palette::new(base, Color::WHITE);
+        let mut dabs = stroke.stroke_to(Vector2::new(0.0, 0.0), &palette);
+        dabs.extend(stroke.stroke_to(Vector2::new(200.0, 0.0), &palette));
+
+        // Colors are stored in bytes, allow for the rounding
+        for dab in &dabs {
+            let (h, s, v) = dab.color.to_hsv();
+            let hue_distance = (h - h0).abs().min(std::f64::consts::TAU - (h - h0).abs());
+            assert!(hue_distance <= 0.1 * PI + 0.1, "hue {}", h);
+            assert!((s - s0).abs() <= 0.2 + 0.02, "saturation {}", s);
+            assert!((v - v0).abs() <= 0.2 + 0.01, "value {}", v);
+            assert_eq!(dab.color.get_a(), 255);
+        }
+        assert!(dabs.iter().any(|dab| dab.color != base));
+    }
+}
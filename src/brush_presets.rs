@@ -0,0 +1,191 @@
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::brush::{Brush, Dynamics};
+
+const STORAGE_KEY: &str = "paint_wasm.brush_presets";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrushPreset {
+    pub name: String,
+    pub brush: Brush,
+    /// Built-in presets ship with the app and can't be renamed, deleted or exported.
+    #[serde(skip)]
+    pub builtin: bool,
+}
+
+impl BrushPreset {
+    pub fn new(name: &str, brush: Brush) -> Self {
+        Self {
+            name: name.to_string(),
+            brush,
+            builtin: false,
+        }
+    }
+
+    fn builtin(name: &str, brush: Brush) -> Self {
+        Self {
+            builtin: true,
+            ..Self::new(name, brush)
+        }
+    }
+}
+
+/// Built-in presets followed by the user presets, which are kept in `localStorage`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrushLibrary {
+    presets: Vec<BrushPreset>,
+}
+
+impl BrushLibrary {
+    pub fn load() -> Self {
+        let mut presets = builtin_presets();
+        presets.extend(LocalStorage::get::<Vec<BrushPreset>>(STORAGE_KEY).unwrap_or_default());
+        Self { presets }
+    }
+
+    pub fn save(&self) {
+        let user = self.user_presets().cloned().collect::<Vec<_>>();
+        if let Err(e) = LocalStorage::set(STORAGE_KEY, user) {
+            log::error!("Failed to save brush presets: {}", e);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BrushPreset> + '_ {
+        self.presets.iter()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&'_ BrushPreset> {
+        self.presets.get(idx)
+    }
+
+    /// Adds a preset and returns its index.
+    pub fn add(&mut self, preset: BrushPreset) -> usize {
+        self.presets.push(preset);
+        self.save();
+        self.presets.len() - 1
+    }
+
+    pub fn update(&mut self, idx: usize, brush: Brush) {
+        if let Some(preset) = self.presets.get_mut(idx).filter(|p| !p.builtin) {
+            preset.brush = brush;
+            self.save();
+        }
+    }
+
+    pub fn rename(&mut self, idx: usize, name: String) {
+        if let Some(preset) = self.presets.get_mut(idx).filter(|p| !p.builtin) {
+            preset.name = name;
+            self.save();
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if matches!(self.presets.get(idx), Some(p) if !p.builtin) {
+            self.presets.remove(idx);
+            self.save();
+        }
+    }
+
+    pub fn export_json(&self) -> String {
+        let user = self.user_presets().collect::<Vec<_>>();
+        serde_json::to_string_pretty(&user).unwrap()
+    }
+
+    /// Appends presets from a file produced by `export_json`, returns how many were added.
+    pub fn import_json(&mut self, json: &str) -> Result<usize, serde_json::Error> {
+        let imported = serde_json::from_str::<Vec<BrushPreset>>(json)?;
+        let count = imported.len();
+        self.presets.extend(imported);
+        self.save();
+        Ok(count)
+    }
+
+    fn user_presets(&self) -> impl Iterator<Item = &BrushPreset> + '_ {
+        self.presets.iter().filter(|p| !p.builtin)
+    }
+}
+
+pub fn builtin_presets() -> Vec<BrushPreset> {
+    vec![
+        BrushPreset::builtin("Hard round", Brush::default()),
+        BrushPreset::builtin(
+            "Soft round",
+            Brush {
+                hardness: 0.0,
+                ..Brush::default()
+            },
+        ),
+        BrushPreset::builtin(
+            "Pencil",
+            Brush {
+                size: 2.0,
+                spacing: 0.25,
+                opacity: 0.9,
+                ..Brush::default()
+            },
+        ),
+        BrushPreset::builtin(
+            "Marker",
+            Brush {
+                size: 20.0,
+                spacing: 0.05,
+                angle: std::f64::consts::FRAC_PI_4,
+                roundness: 0.3,
+                opacity: 0.5,
+                ..Brush::default()
+            },
+        ),
+        BrushPreset::builtin(
+            "Airbrush",
+            Brush {
+                size: 80.0,
+                spacing: 0.05,
+                hardness: 0.0,
+                opacity: 0.1,
+                dynamics: Dynamics {
+                    scatter: 0.05,
+                    ..Dynamics::default()
+                },
+                ..Brush::default()
+            },
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        brush::BrushStroke,
+        color::{Color, Palette},
+        vector::Vector2,
+    };
+
+    /// Alpha of a `rgba(r, g, b, a)` style.
+    fn style_alpha(style: &str) -> f64 {
+        let inner = style.trim_start_matches("rgba(").trim_end_matches(')');
+        inner.rsplit(", ").next().unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn translucent_preset_paints_translucent() {
+        let marker = builtin_presets()
+            .into_iter()
+            .find(|preset| preset.name == "Marker")
+            .unwrap();
+        let json = serde_json::to_string(&marker).unwrap();
+        let loaded: BrushPreset = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.brush, marker.brush);
+
+        let mut stroke = BrushStroke::new(loaded.brush, 0);
+        let dabs = stroke.stroke_to(Vector2::new(0.0, 0.0), &Palette::default());
+        let alpha = style_alpha(&dabs[0].color.to_style());
+        assert!((alpha - 0.5).abs() < 0.01, "alpha {}", alpha);
+    }
+
+    #[test]
+    fn opaque_style_has_unit_alpha() {
+        assert_eq!(style_alpha(&Color::BLACK.to_style()), 1.0);
+    }
+}
@@ -41,8 +41,67 @@ impl Color {
         Self::from_float(rgb.0 + m, rgb.1 + m, rgb.2 + m, 1.0)
     }
 
+    /// Returns hue in radians, saturation and value, the inverse of `from_hsv`.
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (r, g, b, _) = self.to_float();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let h = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        (h * std::f64::consts::PI / 3.0, s, max)
+    }
+
+    pub fn lerp(self, other: Color, t: f64) -> Self {
+        let (r0, g0, b0, a0) = self.to_float();
+        let (r1, g1, b1, a1) = other.to_float();
+        Self::from_float(
+            r0 + (r1 - r0) * t,
+            g0 + (g1 - g0) * t,
+            b0 + (b1 - b0) * t,
+            a0 + (a1 - a0) * t,
+        )
+    }
+
+    pub fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// CSS color, whose alpha is in `0.0..=1.0`.
     pub fn to_style(self) -> String {
-        format!("rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+        format!(
+            "rgba({}, {}, {}, {})",
+            self.r,
+            self.g,
+            self.b,
+            self.a as f64 / 255.0
+        )
+    }
+
+    /// Parses `#rrggbb` as used by color inputs.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        Some(Self::new(channel(0)?, channel(2)?, channel(4)?, 255))
+    }
+
+    /// Formats as `#rrggbb`, ignoring alpha.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
     pub fn get_r(self) -> u8 {
@@ -57,8 +116,22 @@ impl Color {
         self.b
     }
 
+    pub fn get_a(self) -> u8 {
+        self.a
+    }
+
+    pub fn to_float(self) -> (f64, f64, f64, f64) {
+        (
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0,
+            self.a as f64 / 255.0,
+        )
+    }
+
     pub const BLACK: Color = Self::new(0, 0, 0, 255);
     pub const WHITE: Color = Self::new(255, 255, 255, 255);
+    pub const TRANSPARENT: Color = Self::new(0, 0, 0, 0);
 }
 
 fn f64_to_u8(v: f64) -> u8 {
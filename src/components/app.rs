@@ -1,164 +1,1141 @@
 use crate::{
-    color::{Color, Palette},
+    airbrush::{AirbrushSettings, Sprayer},
+    assistants::{Assistant, Assisted, AssistedStroke},
+    brush::{Brush, BrushStroke, Dab},
+    color::Palette,
     components::{
-        color_picker::ColorPicker, draganddrop_container::DraganddropContainer,
+        assistants_widget::AssistantsWidget,
+        brush_presets_widget::BrushPresetsWidget,
+        canvas_size_dialog::CanvasSizeDialog,
+        color_picker::ColorPicker,
+        document_view::{DocumentView, HANDLE_RADIUS, RULER_SIZE},
+        draganddrop_container::DraganddropContainer,
+        guides_widget::GuidesWidget,
         layers_widget::LayersWidget,
+        navigator_widget::NavigatorWidget,
+        new_document_dialog::NewDocumentDialog,
+        resources_widget::ResourcesWidget,
+        scale_image_dialog::ScaleImageDialog,
+        tools_widget::ToolsWidget,
+        transform_widget::{Target, TransformWidget},
+        view_widget::ViewWidget,
     },
+    crop::{drag_rect, round_rect, CropAspect},
+    document::{Anchor, DocumentSettings, MAX_SIDE},
+    dodge_burn::{DodgeBurn, DAB_SPACING},
+    files::{download, ImageLoader},
+    guides::{Guide, GuideSettings, Orientation, SnapLines},
+    image_transform::{about_centers, rotated_size, Transformation},
     layer_manager::{LayerManager, RcLayerManager},
-    vector::{Rectangle, Vector2},
-    virtual_context::VirtualContext,
+    raster::RgbaImage,
+    redraw::Redraw,
+    resample::Filter,
+    resources::{RcResources, Resources},
+    shape_recognition::{recognize, Shape},
+    tiles::TileGrid,
+    tool::Tool,
+    vector::{Rectangle, Transform, Vector2},
+    viewport::Viewport,
+    virtual_context::{DabStyle, VirtualContext},
 };
-use web_sys::{HtmlCanvasElement, MouseEvent, WheelEvent};
-use yew::{html, Component, Context, NodeRef};
+use gloo::{
+    dialogs::alert,
+    events::EventListener,
+    file::{callbacks::FileReader, File, FileReadError},
+    timers::callback::{Interval, Timeout},
+    utils::window,
+};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, HtmlInputElement, KeyboardEvent, MouseEvent, WheelEvent};
+use yew::{html, Component, Context, Event, NodeRef, TargetCast};
 
 pub struct App {
     my_input: NodeRef,
     palette: Palette,
+    tool: Tool,
+    dodge_burn: DodgeBurn,
+    airbrush: AirbrushSettings,
+    brush: Brush,
     layer_manager: RcLayerManager,
+    /// Incremented for every new document to recreate the components subscribed to
+    /// the previous one.
+    document_id: usize,
+    /// Settings of the last new document, the dialog starts from them.
+    document_settings: DocumentSettings,
+    dialog: Option<Dialog>,
+    resources: RcResources,
+    assistants: Vec<Assistant>,
+    shape_recognition: bool,
+    guide_settings: GuideSettings,
+    crop_aspect: CropAspect,
+    /// Area the crop tool keeps, in document coordinates.
+    crop: Option<Rectangle>,
+    /// Corner the crop area is dragged from.
+    crop_origin: Option<Vector2>,
 
-    canvas_ref: NodeRef,
-    context: Option<VirtualContext>,
     previous_point: Option<Vector2>,
+    stroke_origin: Option<Vector2>,
+    sprayer: Option<Sprayer>,
+    airbrush_timer: Option<Interval>,
+    brush_stroke: Option<BrushStroke>,
+    assisted_stroke: Option<AssistedStroke>,
+    /// Assistant and handle indices of the handle being dragged.
+    dragged_handle: Option<(usize, usize)>,
+    dragged_guide: Option<usize>,
+    stroke_seed: u64,
+    /// Points of a freehand stroke and the layer before it, kept to replace the
+    /// stroke with a recognized shape.
+    stroke_points: Vec<Vector2>,
+    stroke_snapshot: Option<TileGrid>,
+    hold_timer: Option<Timeout>,
+    /// Recognized shape and the handle following the pointer.
+    shape: Option<(Shape, usize)>,
 
-    line_width: f64,
-    scale: f64,
+    views: Vec<Viewport>,
+    /// View last pointed at, controlled by the view panels and shortcuts.
+    active_view: usize,
+    /// Last screen position of the pointer while panning.
+    panning: Option<Vector2>,
+    /// Pointer angle around the middle of the screen and view rotation when
+    /// rotating started.
+    rotating: Option<(f64, f64)>,
+    space_held: bool,
+    rotate_held: bool,
+    /// Pointer events come faster than frames, the views follow them once a frame.
+    render: Redraw,
+    /// Reads the PNG being opened, then decodes its pixels.
+    reader: Option<FileReader>,
+    image_loader: Option<ImageLoader>,
+    _listeners: Vec<EventListener>,
 }
 
 impl App {
-    fn draw(&self) {
-        let context = self.context.as_ref().unwrap();
-        context.checkerboard(self.scaled(), Color::new(191, 191, 191, 255), Color::WHITE);
-        for layer in self.layer_manager.borrow().iter_layers() {
-            context.draw_image_bounded(layer.get_canvas(), Rectangle::new(0.0, 0.0, 1000.0, 500.0));
+    /// Recreates the components sized after the document and fits the views to it,
+    /// keeping their rotation and mirroring.
+    fn document_resized(&mut self) {
+        self.document_id += 1;
+        // Fit once the views are laid out
+        for viewport in self.views.iter_mut() {
+            viewport.size = Vector2::new(0.0, 0.0);
+        }
+    }
+
+    /// Changes the canvas size without scaling, moving the content and everything
+    /// placed over it by `offset`.
+    fn resize_canvas(&mut self, width: u32, height: u32, offset: Vector2) {
+        self.reset_origin();
+        let (width, height) = (width.clamp(1, MAX_SIDE), height.clamp(1, MAX_SIDE));
+        self.layer_manager
+            .borrow_mut()
+            .resize_canvas(width, height, offset);
+        for assistant in self.assistants.iter_mut() {
+            for handle in assistant.handles.iter_mut() {
+                *handle = *handle + offset;
+            }
+        }
+        self.crop = None;
+        self.crop_origin = None;
+        self.document_resized();
+    }
+
+    /// Maps every layer and the assistants through `transform` onto a canvas of `size`.
+    fn transform_document(&mut self, transform: &Transform, size: (u32, u32), smooth: bool) {
+        let resized = size != self.document_pixels();
+        self.layer_manager
+            .borrow_mut()
+            .transform(transform, size.0, size.1, smooth);
+        for assistant in self.assistants.iter_mut() {
+            for handle in assistant.handles.iter_mut() {
+                *handle = transform.apply(*handle);
+            }
+        }
+        self.crop = None;
+        if resized {
+            self.document_resized();
+        }
+    }
+
+    /// Moves an infinite document back so its layers start at zero, which operations
+    /// on the whole canvas expect.
+    fn reset_origin(&mut self) {
+        let shift = self.layer_manager.borrow_mut().reset_origin();
+        if shift == Vector2::new(0.0, 0.0) {
+            return;
+        }
+        for assistant in self.assistants.iter_mut() {
+            for handle in assistant.handles.iter_mut() {
+                *handle = *handle + shift;
+            }
+        }
+        if let Some(crop) = self.crop.as_mut() {
+            crop.coord = crop.coord + shift;
+        }
+        self.document_resized();
+    }
+
+    /// Grows the layers of an infinite document to cover the area about to be painted.
+    fn reserve(&mut self, area: Option<Rectangle>) {
+        let mut manager = self.layer_manager.borrow_mut();
+        let grown = match area {
+            Some(area) => manager.reserve(area),
+            None => None,
+        };
+        if let (Some(bounds), Some(snapshot)) = (grown, self.stroke_snapshot.as_mut()) {
+            manager.grow_selected_tiles(snapshot, bounds);
+        }
+    }
+
+    fn document_pixels(&self) -> (u32, u32) {
+        let manager = self.layer_manager.borrow();
+        (manager.width(), manager.height())
+    }
+
+    fn document_bounds(&self) -> Rectangle {
+        self.layer_manager.borrow().bounds()
+    }
+
+    /// Recognizes the shape of the stroke once the pointer rests for `HOLD_MILLIS`.
+    fn restart_hold_timer(&mut self, ctx: &Context<Self>) {
+        let link = ctx.link().clone();
+        self.hold_timer = Some(Timeout::new(HOLD_MILLIS, move || {
+            link.send_message(Msg::StrokeHold)
+        }));
+    }
+
+    fn request_render(&mut self, ctx: &Context<Self>) {
+        self.render.request(ctx.link(), Msg::Render);
+    }
+
+    /// Indices of the assistant and its handle under the pointer.
+    fn handle_at(&self, view: usize, point: Vector2) -> Option<(usize, usize)> {
+        self.assistants
+            .iter()
+            .enumerate()
+            .filter(|(_, assistant)| assistant.enabled)
+            .flat_map(|(i, assistant)| {
+                assistant
+                    .handles
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, &handle)| ((i, j), (handle - point).len()))
+            })
+            .filter(|(_, distance)| *distance <= HANDLE_RADIUS / self.views[view].zoom)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(indices, _)| indices)
+    }
+
+    /// Index of the guide under the pointer.
+    fn guide_at(&self, view: usize, point: Vector2) -> Option<usize> {
+        self.layer_manager
+            .borrow()
+            .guides()
+            .iter()
+            .map(|guide| guide.distance(point))
+            .enumerate()
+            .filter(|(_, distance)| *distance <= HANDLE_RADIUS / self.views[view].zoom)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+
+    /// Moves the point onto nearby guides, grid lines or layer edges.
+    fn snap(&self, view: usize, point: Vector2) -> Vector2 {
+        let lines = SnapLines::new(
+            &self.guide_settings,
+            self.layer_manager.borrow().guides(),
+            self.document_bounds(),
+        );
+        lines.snap(point, SNAP_DISTANCE / self.views[view].zoom)
+    }
+
+    /// Angle of the pointer around the middle of the view.
+    fn screen_angle(&self, view: usize, e: &MouseEvent) -> f64 {
+        let delta = screen_position(e) - self.views[view].size * 0.5;
+        delta.y.atan2(delta.x)
+    }
+
+    fn over_ruler(&self, e: &MouseEvent) -> bool {
+        let screen = screen_position(e);
+        self.guide_settings.rulers && (screen.x < RULER_SIZE || screen.y < RULER_SIZE)
+    }
+
+    fn pointer_position(&self, view: usize, e: &MouseEvent) -> Vector2 {
+        self.views[view].to_document(screen_position(e))
+    }
+
+    /// Pointer position, locked to `ANGLE_STEP` increments around the stroke origin
+    /// while Shift is held.
+    fn constrained_position(&self, view: usize, e: &MouseEvent) -> Vector2 {
+        let point = self.pointer_position(view, e);
+        match self.stroke_origin {
+            Some(origin) if e.shift_key() => snap_angle(origin, point, ANGLE_STEP),
+            _ => point,
+        }
+    }
+
+    /// Paints the stroke up to `point` bent onto the assistants, returns the new end of
+    /// the stroke or `None` while no guide is picked.
+    fn continue_stroke(&mut self, from: Vector2, point: Vector2) -> Option<Vector2> {
+        let assisted = match self.assisted_stroke.as_mut() {
+            Some(stroke) => stroke.to(&self.assistants, point),
+            None => Assisted::To(point),
+        };
+        match assisted {
+            Assisted::Pending => None,
+            Assisted::Start(start, point) => {
+                self.paint(None, start);
+                self.paint(Some(start), point);
+                Some(point)
+            }
+            Assisted::To(point) => {
+                self.paint(Some(from), point);
+                Some(point)
+            }
         }
     }
 
-    fn scaled(&self) -> f64 {
-        (10.0 / self.scale).ceil()
+    /// Replaces the stroke with the recognized shape painted by the same brush.
+    fn paint_shape(&mut self) {
+        let shape = match (&self.shape, &self.stroke_snapshot) {
+            (Some((shape, _)), Some(_)) => shape,
+            _ => return,
+        };
+        let mut stroke = BrushStroke::new(self.brush, self.stroke_seed);
+        let dabs: Vec<_> = shape
+            .outline()
+            .into_iter()
+            .flat_map(|point| stroke.stroke_to(point, &self.palette))
+            .collect();
+        let area = dabs_area(&dabs);
+        self.reserve(area);
+        let snapshot = match &self.stroke_snapshot {
+            Some(snapshot) => snapshot.clone(),
+            None => return,
+        };
+        let mut manager = self.layer_manager.borrow_mut();
+        manager.restore_selected(snapshot);
+        if let Some(area) = area {
+            manager.draw_in_context(area, |context| self.fill_dabs(context, &dabs));
+        }
     }
+
+    fn fill_dabs(&self, context: &VirtualContext, dabs: &[Dab]) {
+        let mut resources = self.resources.borrow_mut();
+        let grain = self
+            .brush
+            .grain
+            .and_then(|grain| Some((resources.grain_canvas(grain)?, grain.scale)));
+        let style = DabStyle {
+            tip: self.brush.tip.and_then(|id| resources.get_canvas(id)),
+            pattern: self.brush.pattern.and_then(|id| resources.get_canvas(id)),
+            grain: grain.as_ref().map(|(canvas, scale)| (canvas, *scale)),
+            mode: self.brush.mode,
+        };
+        context.fill_dabs(dabs, style);
+    }
+
+    fn paint(&mut self, from: Option<Vector2>, to: Vector2) {
+        let radius = self.brush.size / 2.0;
+
+        let particles = match (self.tool, self.sprayer.as_mut()) {
+            (Tool::Spray, Some(sprayer)) => sprayer.particles(to, radius, self.airbrush.density),
+            _ => vec![],
+        };
+
+        let dabs = match (self.tool, self.brush_stroke.as_mut()) {
+            (Tool::Brush, Some(stroke)) => stroke.stroke_to(to, &self.palette),
+            _ => vec![],
+        };
+
+        // Soft circles along the segment, the same points bound the area and get painted
+        let step = match self.tool {
+            Tool::Dodge | Tool::Burn | Tool::Sponge => radius * DAB_SPACING,
+            _ => radius / 4.0,
+        };
+        let points = segment_points(from, to, step);
+
+        let area = match self.tool {
+            Tool::Brush => dabs_area(&dabs),
+            Tool::Spray => covering(particles.iter().copied(), self.airbrush.particle_size),
+            Tool::Airbrush | Tool::Dodge | Tool::Burn | Tool::Sponge => {
+                covering(points.iter().copied(), radius)
+            }
+            Tool::Assistant | Tool::Crop => None,
+        };
+        let area = match area {
+            Some(area) => area,
+            None => return,
+        };
+        // Dodge and burn only change painted pixels, they never need more room
+        if !matches!(self.tool, Tool::Dodge | Tool::Burn | Tool::Sponge) {
+            self.reserve(Some(area));
+        }
+
+        let this = &*self;
+        this.layer_manager
+            .borrow_mut()
+            .draw_in_context(area, |context| match this.tool {
+                Tool::Brush => this.fill_dabs(context, &dabs),
+                Tool::Airbrush => {
+                    let color = this.airbrush.deposit_color(this.palette.main);
+                    for point in &points {
+                        context.fill_soft_circle(point.x, point.y, radius, color);
+                    }
+                }
+                Tool::Spray => {
+                    context.fill_circles(
+                        &particles,
+                        this.airbrush.particle_size,
+                        this.palette.main,
+                    );
+                }
+                Tool::Dodge | Tool::Burn | Tool::Sponge => {
+                    let mode = this.tool.tone_mode(&this.dodge_burn).unwrap();
+                    let settings = this.dodge_burn.per_dab();
+                    for &point in &points {
+                        context.dodge_burn(point, radius, mode, settings);
+                    }
+                }
+                Tool::Assistant | Tool::Crop => {}
+            });
+    }
+}
+
+/// Distance in screen pixels from which points snap to guides.
+const SNAP_DISTANCE: f64 = 8.0;
+
+/// Zoom change of one wheel step or shortcut.
+const ZOOM_STEP: f64 = 1.1;
+
+const HOLD_MILLIS: u32 = 600;
+
+/// Angle increment of strokes drawn with Shift held.
+const ANGLE_STEP: f64 = std::f64::consts::PI / 12.0;
+
+/// Bounding box of circles of `radius` around the points.
+fn covering(points: impl IntoIterator<Item = Vector2>, radius: f64) -> Option<Rectangle> {
+    let mut points = points.into_iter();
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), p| {
+        (
+            Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+            Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+        )
+    });
+    let size = max - min + Vector2::new(radius, radius) * 2.0;
+    Some(Rectangle::new(
+        min.x - radius,
+        min.y - radius,
+        size.x,
+        size.y,
+    ))
+}
+
+fn dabs_area(dabs: &[Dab]) -> Option<Rectangle> {
+    let radius = dabs
+        .iter()
+        .map(|dab| dab.shape.radii.x.max(dab.shape.radii.y))
+        .fold(0.0, f64::max);
+    covering(dabs.iter().map(|dab| dab.shape.center), radius)
+}
+
+fn screen_position(e: &MouseEvent) -> Vector2 {
+    Vector2::new(e.offset_x() as f64, e.offset_y() as f64)
+}
+
+/// Keys typed into form fields are not shortcuts.
+fn is_typing(e: &KeyboardEvent) -> bool {
+    e.target()
+        .and_then(|target| target.dyn_into::<HtmlElement>().ok())
+        .map(|element| matches!(element.tag_name().as_str(), "INPUT" | "SELECT" | "TEXTAREA"))
+        .unwrap_or(false)
+}
+
+/// Projects `point` onto the nearest ray from `origin` at a multiple of `step` radians.
+fn snap_angle(origin: Vector2, point: Vector2, step: f64) -> Vector2 {
+    let delta = point - origin;
+    let angle = (delta.y.atan2(delta.x) / step).round() * step;
+    let direction = Vector2::new(angle.cos(), angle.sin());
+    origin + direction * delta.dot(direction)
+}
+
+/// Points along the segment spaced by at most `step`, excluding `from` and including `to`.
+fn segment_points(from: Option<Vector2>, to: Vector2, step: f64) -> Vec<Vector2> {
+    let from = match from {
+        Some(from) => from,
+        None => return vec![to],
+    };
+    let steps = ((to - from).len() / step.max(1.0)).ceil().max(1.0) as usize;
+    (1..=steps)
+        .map(|i| from + (to - from) * (i as f64 / steps as f64))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialog {
+    NewDocument,
+    ScaleImage,
+    CanvasSize,
 }
 
 pub enum Msg {
     ColorPicked(Palette),
-    MouseDown(MouseEvent),
-    MouseMove(MouseEvent),
-    MouseUp(MouseEvent),
-    Wheel(WheelEvent),
+    ToolPicked(Tool),
+    DodgeBurnChanged(DodgeBurn),
+    AirbrushChanged(AirbrushSettings),
+    AirbrushTick,
+    Render,
+    BrushChanged(Brush),
+    AssistantsChanged(Vec<Assistant>),
+    ShapeRecognitionChanged(bool),
+    GuideSettingsChanged(GuideSettings),
+    ClearGuides,
+    OpenDialog(Dialog),
+    CloseDialog,
+    NewDocument(DocumentSettings),
+    ScaleImage(u32, u32, Filter),
+    ResizeCanvas(u32, u32, Anchor),
+    Trim,
+    ExportImage,
+    OpenImage(Option<web_sys::File>),
+    ImageFileRead(web_sys::File, Result<Vec<u8>, FileReadError>),
+    ImageOpened(Vec<u8>, Option<RgbaImage>),
+    Transform(Target, Transformation),
+    /// Rotates by an angle in radians, growing the canvas to fit if asked.
+    Rotate(Target, f64, bool),
+    CropAspectChanged(CropAspect),
+    ApplyCrop,
+    CancelCrop,
+    StrokeHold,
+    MouseDown(usize, MouseEvent),
+    MouseMove(usize, MouseEvent),
+    MouseUp(usize, MouseEvent),
+    Wheel(usize, WheelEvent),
+    KeyDown(KeyboardEvent),
+    KeyUp(KeyboardEvent),
+    ViewportChanged(usize, Viewport),
+    SplitChanged(bool),
+    Resize,
 }
 
 impl Component for App {
     type Message = Msg;
     type Properties = ();
 
-    fn create(_ctx: &yew::Context<Self>) -> Self {
-        let layer_manager: RcLayerManager = LayerManager::new(1000, 500).into();
-        layer_manager.borrow_mut().push_layer();
-        layer_manager.borrow_mut().push_layer();
-        layer_manager.borrow_mut().push_layer();
-        layer_manager.borrow_mut().push_layer();
-        layer_manager.borrow_mut().push_layer();
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        let document_settings = DocumentSettings::default();
+        let layer_manager: RcLayerManager = LayerManager::from_settings(&document_settings).into();
+
+        let listen = |event: &'static str, msg: fn(KeyboardEvent) -> Msg| {
+            let link = ctx.link().clone();
+            EventListener::new(&window(), event, move |e| {
+                link.send_message(msg(e.clone().unchecked_into()))
+            })
+        };
+        let resize = {
+            let link = ctx.link().clone();
+            EventListener::new(&window(), "resize", move |_| link.send_message(Msg::Resize))
+        };
+        let listeners = vec![
+            listen("keydown", Msg::KeyDown),
+            listen("keyup", Msg::KeyUp),
+            resize,
+        ];
+
         Self {
             my_input: NodeRef::default(),
             palette: Palette::default(),
+            tool: Tool::default(),
+            dodge_burn: DodgeBurn::default(),
+            airbrush: AirbrushSettings::default(),
+            brush: Brush::default(),
             layer_manager,
-            canvas_ref: NodeRef::default(),
-            context: None,
+            document_id: 0,
+            document_settings,
+            dialog: None,
+            resources: Resources::load().into(),
+            assistants: vec![],
+            shape_recognition: false,
+            guide_settings: GuideSettings::default(),
+            crop_aspect: CropAspect::default(),
+            crop: None,
+            crop_origin: None,
             previous_point: None,
-            line_width: 50.0,
-            scale: 1.0,
+            stroke_origin: None,
+            sprayer: None,
+            airbrush_timer: None,
+            brush_stroke: None,
+            assisted_stroke: None,
+            dragged_handle: None,
+            dragged_guide: None,
+            stroke_seed: 0,
+            stroke_points: vec![],
+            stroke_snapshot: None,
+            hold_timer: None,
+            shape: None,
+            views: vec![Viewport::new(Vector2::new(0.0, 0.0))],
+            active_view: 0,
+            panning: None,
+            rotating: None,
+            space_held: false,
+            rotate_held: false,
+            render: Redraw::default(),
+            reader: None,
+            image_loader: None,
+            _listeners: listeners,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Msg::ColorPicked(palette) => {
                 self.palette = palette;
                 false
             }
-            Msg::MouseDown(e) => {
-                self.layer_manager.borrow().draw_in_context(|context| {
-                    context.fill_circle(
-                        e.offset_x() as f64 / self.scale,
-                        e.offset_y() as f64 / self.scale,
-                        self.line_width / 2.0,
-                        self.palette.main,
-                    );
-                });
+            Msg::ToolPicked(tool) => {
+                self.tool = tool;
+                if tool != Tool::Crop {
+                    self.crop = None;
+                }
+                true
+            }
+            Msg::DodgeBurnChanged(dodge_burn) => {
+                self.dodge_burn = dodge_burn;
+                true
+            }
+            Msg::AirbrushChanged(airbrush) => {
+                self.airbrush = airbrush;
+                true
+            }
+            Msg::BrushChanged(brush) => {
+                self.brush = brush;
+                true
+            }
+            Msg::AssistantsChanged(assistants) => {
+                self.assistants = assistants;
+                true
+            }
+            Msg::ShapeRecognitionChanged(enabled) => {
+                self.shape_recognition = enabled;
+                true
+            }
+            Msg::GuideSettingsChanged(settings) => {
+                self.guide_settings = settings;
+                true
+            }
+            Msg::ClearGuides => {
+                self.layer_manager.borrow_mut().guides_mut().clear();
+                true
+            }
+            Msg::OpenDialog(dialog) => {
+                self.dialog = Some(dialog);
+                true
+            }
+            Msg::CloseDialog => {
+                self.dialog = None;
+                true
+            }
+            Msg::NewDocument(settings) => {
+                self.layer_manager = LayerManager::from_settings(&settings).into();
+                self.document_settings = settings;
+                self.dialog = None;
+                self.document_resized();
+                true
+            }
+            Msg::ScaleImage(width, height, filter) => {
+                self.reset_origin();
+                self.layer_manager.borrow_mut().scale(width, height, filter);
+                self.dialog = None;
+                self.document_resized();
+                true
+            }
+            Msg::ResizeCanvas(width, height, anchor) => {
+                let offset = anchor.offset(self.document_pixels(), (width, height));
+                self.dialog = None;
+                self.resize_canvas(width, height, offset);
+                true
+            }
+            Msg::Trim => {
+                let bounds = self.layer_manager.borrow().trim_bounds();
+                match bounds {
+                    Some((x, y, width, height)) => {
+                        let offset = Vector2::new(-(x as f64), -(y as f64));
+                        self.resize_canvas(width, height, offset);
+                        true
+                    }
+                    // Nothing but a uniform color, no borders to tell apart
+                    None => false,
+                }
+            }
+            Msg::ExportImage => {
+                let png = self.layer_manager.borrow().export_png();
+                download("image.png", "image/png", &png);
+                false
+            }
+            Msg::OpenImage(file) => {
+                if let Some(file) = file {
+                    let on_load = ctx
+                        .link()
+                        .callback(|(file, result)| Msg::ImageFileRead(file, result));
+                    let read = file.clone();
+                    self.reader = Some(gloo::file::callbacks::read_as_bytes(
+                        &File::from(read),
+                        move |result| on_load.emit((file, result)),
+                    ));
+                }
+                false
+            }
+            Msg::ImageFileRead(file, result) => {
+                self.reader = None;
+                match result {
+                    Ok(bytes) => {
+                        // The browser decodes the pixels, the bytes hold the guides
+                        let on_load = ctx
+                            .link()
+                            .callback(move |image| Msg::ImageOpened(bytes.clone(), image));
+                        self.image_loader =
+                            Some(ImageLoader::new(&file, move |image| on_load.emit(image)));
+                    }
+                    Err(e) => alert(&format!("Failed to read the file: {}", e)),
+                }
+                false
+            }
+            Msg::ImageOpened(bytes, image) => {
+                self.image_loader = None;
+                let image = match image {
+                    Some(image) if image.width <= MAX_SIDE && image.height <= MAX_SIDE => image,
+                    Some(image) => {
+                        alert(&format!(
+                            "Images up to {} × {} px can be opened, this one is {} × {} px",
+                            MAX_SIDE, MAX_SIDE, image.width, image.height
+                        ));
+                        return false;
+                    }
+                    None => {
+                        alert("The file isn't an image");
+                        return false;
+                    }
+                };
+                self.layer_manager = LayerManager::from_png(&bytes, &image).into();
+                self.document_resized();
+                true
+            }
+            Msg::Transform(target, transformation) => {
+                self.reset_origin();
+                let size = self.document_pixels();
+                match target {
+                    Target::Image => {
+                        let to = transformation.size(size);
+                        let transform = about_centers(transformation.linear(), size, to);
+                        self.transform_document(&transform, to, false);
+                    }
+                    Target::SelectedLayer => {
+                        // Turned around the middle, whatever sticks out is cut off
+                        let transform = about_centers(transformation.linear(), size, size);
+                        self.layer_manager
+                            .borrow_mut()
+                            .transform_selected(&transform, false);
+                    }
+                }
+                true
+            }
+            Msg::Rotate(target, angle, expand) => {
+                self.reset_origin();
+                let size = self.document_pixels();
+                let to = if expand {
+                    rotated_size(size, angle)
+                } else {
+                    size
+                };
+                match target {
+                    Target::Image => {
+                        let transform = about_centers(Transform::rotate(angle), size, to);
+                        self.transform_document(&transform, to, true);
+                    }
+                    Target::SelectedLayer => {
+                        if to != size {
+                            let offset = Anchor::CENTER.offset(size, to);
+                            self.resize_canvas(to.0, to.1, offset);
+                        }
+                        let transform = about_centers(Transform::rotate(angle), to, to);
+                        self.layer_manager
+                            .borrow_mut()
+                            .transform_selected(&transform, true);
+                    }
+                }
+                true
+            }
+            Msg::CropAspectChanged(aspect) => {
+                self.crop_aspect = aspect;
+                if let Some(crop) = self.crop {
+                    let ratio = aspect.ratio(self.document_bounds().size);
+                    self.crop = Some(drag_rect(crop.coord, crop.coord + crop.size, ratio));
+                }
+                true
+            }
+            Msg::ApplyCrop => {
+                if self.crop.is_none() {
+                    return false;
+                }
+                // Moves the crop area along, its position has to be in layer pixels
+                self.reset_origin();
+                if let Some(crop) = self.crop {
+                    let (x, y, width, height) = round_rect(crop);
+                    self.resize_canvas(width, height, Vector2::new(-x as f64, -y as f64));
+                }
+                true
+            }
+            Msg::CancelCrop => {
+                self.crop = None;
+                self.crop_origin = None;
+                true
+            }
+            Msg::StrokeHold => {
+                self.hold_timer = None;
+                if let (Some(point), None) = (self.previous_point, &self.shape) {
+                    if let Some(shape) = recognize(&self.stroke_points) {
+                        self.shape = Some((shape, shape.closest_handle(point)));
+                        self.paint_shape();
+                    }
+                }
+                false
+            }
+            Msg::Render => {
+                self.render.done();
+                true
+            }
+            Msg::AirbrushTick => {
+                let pending = matches!(&self.assisted_stroke, Some(stroke) if stroke.is_pending());
+                if let (Some(point), false) = (self.previous_point, pending) {
+                    self.paint(None, point);
+                }
+                false
+            }
+            Msg::MouseDown(view, e) => {
+                self.active_view = view;
 
-                self.previous_point = Some(Vector2::new(
-                    e.offset_x() as f64 / self.scale,
-                    e.offset_y() as f64 / self.scale,
-                ));
+                if e.button() == 1 || self.space_held {
+                    e.prevent_default();
+                    self.panning = Some(screen_position(&e));
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if self.rotate_held {
+                    let angle = self.screen_angle(view, &e);
+                    self.rotating = Some((angle, self.views[view].rotation));
+                    self.request_render(ctx);
+                    return false;
+                }
 
-                self.draw();
+                let point = self.pointer_position(view, &e);
+
+                if self.over_ruler(&e) {
+                    // Pulls a new guide out of the ruler
+                    let orientation = if screen_position(&e).y < RULER_SIZE {
+                        Orientation::Horizontal
+                    } else {
+                        Orientation::Vertical
+                    };
+                    let mut guide = Guide::new(orientation, 0.0);
+                    guide.move_to(point);
+                    let mut manager = self.layer_manager.borrow_mut();
+                    manager.guides_mut().push(guide);
+                    self.dragged_guide = Some(manager.guides().len() - 1);
+                    drop(manager);
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if self.tool == Tool::Crop {
+                    let origin = self.snap(view, point);
+                    self.crop_origin = Some(origin);
+                    self.crop = Some(Rectangle::new(origin.x, origin.y, 0.0, 0.0));
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if self.tool == Tool::Assistant {
+                    self.dragged_handle = self.handle_at(view, point);
+                    if self.dragged_handle.is_none() {
+                        self.dragged_guide = self.guide_at(view, point);
+                    }
+                    return false;
+                }
+
+                // Shift-click draws a straight line from the end of the last stroke
+                let line_from = if e.shift_key() {
+                    self.layer_manager
+                        .borrow()
+                        .get_selected()
+                        .and_then(|layer| layer.get_last_point())
+                } else {
+                    None
+                };
+
+                if self.tool == Tool::Brush {
+                    self.stroke_seed = rand::random();
+                    let mut stroke = BrushStroke::new(self.brush, self.stroke_seed);
+                    if let Some(from) = line_from {
+                        stroke.move_to(from);
+                    }
+                    self.brush_stroke = Some(stroke);
+                }
+
+                if self.tool.is_continuous() {
+                    self.sprayer = Some(Sprayer::from_settings(&self.airbrush));
+                    let link = ctx.link().clone();
+                    self.airbrush_timer =
+                        Some(Interval::new(self.airbrush.interval_millis(), move || {
+                            link.send_message(Msg::AirbrushTick)
+                        }));
+                }
+
+                if line_from.is_none() && self.assistants.iter().any(|a| a.enabled) {
+                    // Painting starts once the stroke picks its guide
+                    self.assisted_stroke = Some(AssistedStroke::new(point));
+                } else {
+                    if line_from.is_none() && self.tool == Tool::Brush && self.shape_recognition {
+                        self.stroke_snapshot = self.layer_manager.borrow().selected_tiles();
+                        self.stroke_points = vec![point];
+                        self.restart_hold_timer(ctx);
+                    }
+                    self.paint(line_from, point);
+                }
+
+                self.previous_point = Some(point);
+                self.stroke_origin = Some(point);
 
                 false
             }
-            Msg::MouseMove(e) => {
-                if let Some(prev) = self.previous_point {
-                    self.layer_manager.borrow().draw_in_context(|context| {
-                        context.fill_circle(
-                            e.offset_x() as f64 / self.scale,
-                            e.offset_y() as f64 / self.scale,
-                            self.line_width / 2.0,
-                            self.palette.main,
-                        );
-                        context.line(
-                            prev.x,
-                            prev.y,
-                            e.offset_x() as f64 / self.scale,
-                            e.offset_y() as f64 / self.scale,
-                            self.line_width,
-                            self.palette.main,
-                        );
-                    });
-
-                    self.previous_point = Some(Vector2::new(
-                        e.offset_x() as f64 / self.scale,
-                        e.offset_y() as f64 / self.scale,
-                    ));
+            Msg::MouseMove(view, e) => {
+                if let Some(from) = self.panning {
+                    if view == self.active_view {
+                        let to = screen_position(&e);
+                        self.views[view].pan(to - from);
+                        self.panning = Some(to);
+                    }
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some((start_angle, start_rotation)) = self.rotating {
+                    if view == self.active_view {
+                        let angle = self.screen_angle(view, &e);
+                        self.views[view].set_rotation(start_rotation + angle - start_angle);
+                    }
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some(i) = self.dragged_guide {
+                    let point = self.pointer_position(view, &e);
+                    self.layer_manager.borrow_mut().guides_mut()[i].move_to(point);
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some(origin) = self.crop_origin {
+                    let point = self.snap(view, self.pointer_position(view, &e));
+                    let ratio = self.crop_aspect.ratio(self.document_bounds().size);
+                    self.crop = Some(drag_rect(origin, point, ratio));
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some((i, j)) = self.dragged_handle {
+                    let point = self.snap(view, self.pointer_position(view, &e));
+                    self.assistants[i].handles[j] = point;
+                    self.request_render(ctx);
+                    return false;
+                }
 
-                    self.draw();
+                if let Some((mut shape, handle)) = self.shape {
+                    let point = self.snap(view, self.pointer_position(view, &e));
+                    shape.move_handle(handle, point);
+                    self.shape = Some((shape, handle));
+                    self.paint_shape();
+                    return false;
+                }
+
+                if let Some(prev) = self.previous_point {
+                    let point = self.constrained_position(view, &e);
+                    if let Some(point) = self.continue_stroke(prev, point) {
+                        self.previous_point = Some(point);
+                        if self.stroke_snapshot.is_some() {
+                            self.stroke_points.push(point);
+                            self.restart_hold_timer(ctx);
+                        }
+                    }
                 }
 
                 false
             }
-            Msg::MouseUp(e) => {
-                self.layer_manager.borrow().draw_in_context(|context| {
-                    context.fill_circle(
-                        e.offset_x() as f64 / self.scale,
-                        e.offset_y() as f64 / self.scale,
-                        self.line_width / 2.0,
-                        self.palette.main,
-                    );
-                });
+            Msg::MouseUp(view, e) => {
+                if self.panning.take().is_some() || self.rotating.take().is_some() {
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some(i) = self.dragged_guide.take() {
+                    // Guides dropped back onto the rulers are removed
+                    if self.over_ruler(&e) {
+                        self.layer_manager.borrow_mut().guides_mut().remove(i);
+                    }
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if self.dragged_handle.take().is_some() {
+                    return false;
+                }
+
+                if self.crop_origin.take().is_some() {
+                    // A click without a drag drops the crop area
+                    if matches!(self.crop, Some(crop) if crop.size.x < 1.0 || crop.size.y < 1.0) {
+                        self.crop = None;
+                    }
+                    self.request_render(ctx);
+                    return false;
+                }
+
+                if let Some(prev) = self.previous_point {
+                    let pending =
+                        matches!(&self.assisted_stroke, Some(stroke) if stroke.is_pending());
+                    let end = if let Some((shape, handle)) = self.shape {
+                        shape.handles()[handle]
+                    } else if pending {
+                        // A click without a move, nothing is painted yet
+                        self.paint(None, prev);
+                        prev
+                    } else if self.tool == Tool::Brush {
+                        let point = self.constrained_position(view, &e);
+                        self.continue_stroke(prev, point).unwrap_or(prev)
+                    } else {
+                        prev
+                    };
+                    self.layer_manager.borrow_mut().set_last_point(end);
+                }
 
                 self.previous_point = None;
+                self.stroke_origin = None;
+                self.sprayer = None;
+                self.airbrush_timer = None;
+                self.brush_stroke = None;
+                self.assisted_stroke = None;
+                self.stroke_points.clear();
+                self.stroke_snapshot = None;
+                self.hold_timer = None;
+                self.shape = None;
 
-                self.draw();
+                false
+            }
+            Msg::Wheel(view, e) => {
+                e.prevent_default();
+                self.active_view = view;
+                let viewport = &mut self.views[view];
 
+                if e.ctrl_key() {
+                    let factor = if e.delta_y() < 0.0 {
+                        ZOOM_STEP
+                    } else {
+                        1.0 / ZOOM_STEP
+                    };
+                    viewport.zoom_at(screen_position(&e), factor);
+                } else if e.shift_key() {
+                    viewport.pan(Vector2::new(-e.delta_y(), -e.delta_x()));
+                } else {
+                    viewport.pan(Vector2::new(-e.delta_x(), -e.delta_y()));
+                }
+                self.request_render(ctx);
                 false
             }
-            Msg::Wheel(e) => {
-                if !e.ctrl_key() {
+            Msg::KeyDown(e) => {
+                if is_typing(&e) {
                     return false;
                 }
-
+                let document = self.document_bounds();
+                let viewport = &mut self.views[self.active_view];
+                let center = viewport.size * 0.5;
+                match (e.ctrl_key() || e.meta_key(), e.key().as_str()) {
+                    (false, " ") => {
+                        e.prevent_default();
+                        let changed = !self.space_held;
+                        self.space_held = true;
+                        return changed;
+                    }
+                    (false, "r" | "R") => {
+                        let changed = !self.rotate_held;
+                        self.rotate_held = true;
+                        return changed;
+                    }
+                    (false, "Enter") if self.crop.is_some() => {
+                        ctx.link().send_message(Msg::ApplyCrop);
+                    }
+                    (false, "Escape") if self.crop.is_some() => {
+                        ctx.link().send_message(Msg::CancelCrop);
+                    }
+                    (true, "0") => viewport.fit(document),
+                    (true, "1") => viewport.actual_size(document),
+                    (true, "=" | "+") => viewport.zoom_at(center, ZOOM_STEP),
+                    (true, "-") => viewport.zoom_at(center, 1.0 / ZOOM_STEP),
+                    _ => return false,
+                }
                 e.prevent_default();
-
-                if e.delta_y() < 0.0 {
-                    self.scale *= 1.05;
+                true
+            }
+            Msg::KeyUp(e) => match e.key().as_str() {
+                " " => {
+                    self.space_held = false;
+                    true
+                }
+                "r" | "R" => {
+                    self.rotate_held = false;
+                    true
+                }
+                _ => false,
+            },
+            Msg::ViewportChanged(view, viewport) => {
+                self.views[view] = viewport;
+                true
+            }
+            Msg::SplitChanged(split) => {
+                if split {
+                    // Sized and fit by the view once it's laid out
+                    self.views.push(Viewport::new(Vector2::new(0.0, 0.0)));
                 } else {
-                    self.scale /= 1.05;
+                    self.views.truncate(1);
+                    self.active_view = 0;
                 }
-
-                self.draw();
                 true
             }
+            // The views match their canvases to the new layout when rendered
+            Msg::Resize => true,
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> yew::Html {
+        let cursor = if self.panning.is_some() || self.rotating.is_some() {
+            "grabbing"
+        } else if self.space_held || self.rotate_held {
+            "grab"
+        } else if self.tool == Tool::Crop {
+            "crosshair"
+        } else {
+            "default"
+        };
+        let active = self.active_view;
+        let split = self.views.len() > 1;
+        let document = self.document_id;
+        let document_bounds = self.document_bounds();
+        let (width, height, resolution, infinite) = {
+            let manager = self.layer_manager.borrow();
+            (
+                manager.width(),
+                manager.height(),
+                manager.resolution(),
+                manager.is_infinite(),
+            )
+        };
         html! {
             <div ref={self.my_input.clone()} class="main-container" style={"height: 100vh"}>
                 <DraganddropContainer
@@ -170,6 +1147,202 @@ impl Component for App {
                         color_pick={ctx.link().callback(Msg::ColorPicked)}
                     />
                 </DraganddropContainer>
+                <DraganddropContainer
+                    text="Tools"
+                    event_target={self.my_input.clone()}
+                    key="tools"
+                    top=250
+                >
+                    <ToolsWidget
+                        tool={self.tool}
+                        dodge_burn={self.dodge_burn}
+                        airbrush={self.airbrush}
+                        brush={self.brush}
+                        shape_recognition={self.shape_recognition}
+                        crop_aspect={self.crop_aspect}
+                        tool_pick={ctx.link().callback(Msg::ToolPicked)}
+                        dodge_burn_change={ctx.link().callback(Msg::DodgeBurnChanged)}
+                        airbrush_change={ctx.link().callback(Msg::AirbrushChanged)}
+                        brush_change={ctx.link().callback(Msg::BrushChanged)}
+                        shape_recognition_change={ctx.link().callback(Msg::ShapeRecognitionChanged)}
+                        crop_aspect_change={ctx.link().callback(Msg::CropAspectChanged)}
+                        crop_apply={ctx.link().callback(|_| Msg::ApplyCrop)}
+                        crop_cancel={ctx.link().callback(|_| Msg::CancelCrop)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Brushes"
+                    event_target={self.my_input.clone()}
+                    key="brushes"
+                    top=500
+                >
+                    <BrushPresetsWidget
+                        brush={self.brush}
+                        brush_pick={ctx.link().callback(Msg::BrushChanged)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Tips & patterns"
+                    event_target={self.my_input.clone()}
+                    key="resources"
+                    top=500
+                    left=250
+                >
+                    <ResourcesWidget
+                        manager={self.layer_manager.clone()}
+                        resources={self.resources.clone()}
+                        brush={self.brush}
+                        brush_change={ctx.link().callback(Msg::BrushChanged)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Assistants"
+                    event_target={self.my_input.clone()}
+                    key="assistants"
+                    top=250
+                    left=250
+                >
+                    <AssistantsWidget
+                        assistants={self.assistants.clone()}
+                        center={document_bounds.coord + document_bounds.size * 0.5}
+                        assistants_change={ctx.link().callback(Msg::AssistantsChanged)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Navigator"
+                    event_target={self.my_input.clone()}
+                    key="navigator"
+                    left=750
+                >
+                    <NavigatorWidget
+                        key={document}
+                        manager={self.layer_manager.clone()}
+                        viewport={self.views[active]}
+                        viewport_change={ctx.link().callback(move |v| Msg::ViewportChanged(active, v))}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="View"
+                    event_target={self.my_input.clone()}
+                    key="view"
+                    top=500
+                    left=500
+                >
+                    <ViewWidget
+                        viewport={self.views[active]}
+                        document={document_bounds}
+                        split={self.views.len() > 1}
+                        viewport_change={ctx.link().callback(move |v| Msg::ViewportChanged(active, v))}
+                        split_change={ctx.link().callback(Msg::SplitChanged)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Guides & grid"
+                    event_target={self.my_input.clone()}
+                    key="guides"
+                    top=250
+                    left=500
+                >
+                    <GuidesWidget
+                        settings={self.guide_settings}
+                        settings_change={ctx.link().callback(Msg::GuideSettingsChanged)}
+                        guides_clear={ctx.link().callback(|_| Msg::ClearGuides)}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Rotate & flip"
+                    event_target={self.my_input.clone()}
+                    key="transform"
+                    top=250
+                    left=750
+                >
+                    <TransformWidget
+                        transform={ctx.link().callback(|(target, transformation)| {
+                            Msg::Transform(target, transformation)
+                        })}
+                        rotate={ctx.link().callback(|(target, angle, expand)| {
+                            Msg::Rotate(target, angle, expand)
+                        })}
+                    />
+                </DraganddropContainer>
+                <DraganddropContainer
+                    text="Image"
+                    event_target={self.my_input.clone()}
+                    key="image"
+                    top=500
+                    left=750
+                >
+                    <div class="tools__settings">
+                        <div>
+                            {format!("{} × {} px, {} dpi", width, height, resolution)}
+                            {if infinite { ", infinite" } else { "" }}
+                        </div>
+                        <button onclick={ctx.link().callback(|_| Msg::OpenDialog(Dialog::NewDocument))}>
+                            {"New document…"}
+                        </button>
+                        <label class="presets__import">
+                            {"Open PNG…"}
+                            <input
+                                type="file"
+                                accept="image/png"
+                                onchange={ctx.link().callback(|e: Event| {
+                                    let input: HtmlInputElement = e.target_unchecked_into();
+                                    let file = input.files().and_then(|files| files.get(0));
+                                    // Lets the same file be picked again
+                                    input.set_value("");
+                                    Msg::OpenImage(file)
+                                })}
+                            />
+                        </label>
+                        <button onclick={ctx.link().callback(|_| Msg::OpenDialog(Dialog::ScaleImage))}>
+                            {"Scale image…"}
+                        </button>
+                        <button onclick={ctx.link().callback(|_| Msg::OpenDialog(Dialog::CanvasSize))}>
+                            {"Canvas size…"}
+                        </button>
+                        <button
+                            title="Crop away transparent or uniformly colored borders"
+                            onclick={ctx.link().callback(|_| Msg::Trim)}
+                        >
+                            {"Trim"}
+                        </button>
+                        <button onclick={ctx.link().callback(|_| Msg::ExportImage)}>
+                            {"Export PNG"}
+                        </button>
+                    </div>
+                </DraganddropContainer>
+                {
+                    match self.dialog {
+                        Some(Dialog::NewDocument) => html! {
+                            <NewDocumentDialog
+                                settings={self.document_settings}
+                                create={ctx.link().callback(Msg::NewDocument)}
+                                cancel={ctx.link().callback(|_| Msg::CloseDialog)}
+                            />
+                        },
+                        Some(Dialog::ScaleImage) => html! {
+                            <ScaleImageDialog
+                                width={width}
+                                height={height}
+                                scale={ctx.link().callback(|(width, height, filter)| {
+                                    Msg::ScaleImage(width, height, filter)
+                                })}
+                                cancel={ctx.link().callback(|_| Msg::CloseDialog)}
+                            />
+                        },
+                        Some(Dialog::CanvasSize) => html! {
+                            <CanvasSizeDialog
+                                width={width}
+                                height={height}
+                                resize={ctx.link().callback(|(width, height, anchor)| {
+                                    Msg::ResizeCanvas(width, height, anchor)
+                                })}
+                                cancel={ctx.link().callback(|_| Msg::CloseDialog)}
+                            />
+                        },
+                        None => html! {},
+                    }
+                }
                 <DraganddropContainer
                     text="Layers"
                     event_target={self.my_input.clone()}
@@ -177,44 +1350,51 @@ impl Component for App {
                     left=500
                 >
                     <LayersWidget
+                        key={document}
                         manager={self.layer_manager.clone()}
                     />
                 </DraganddropContainer>
                 <div style="
+                    display: flex;
                     height: 100vh;
                     width: 100vw;
-                    overflow: scroll;
-                    display: flex;
-                    justify-content: center;
-                    align-items: center;
+                    overflow: hidden;
                 ">
-                    <div>
-                        <canvas
-                            style={format!("
-                                background-color: white;
-                                width: {}px;
-                                height: {}px;
-                                image-rendering: crisp-edges;
-                                image-rendering: pixelated;
-                            ", (1000.0 * self.scale) as u32, (500.0 * self.scale) as u32)}
-                            ref={self.canvas_ref.clone()}
-                            onmousedown={ctx.link().callback(Msg::MouseDown)}
-                            onmousemove={ctx.link().callback(Msg::MouseMove)}
-                            onmouseup={ctx.link().callback(Msg::MouseUp)}
-                            onwheel={ctx.link().callback(Msg::Wheel)}
-                        />
-                    </div>
+                    {
+                        for self.views.iter().enumerate().map(|(i, &viewport)| html! {
+                            <div
+                                key={i}
+                                style={format!("
+                                    flex: 1;
+                                    min-width: 0;
+                                    border-left: {};
+                                    outline: {};
+                                    outline-offset: -2px;
+                                ",
+                                    if i > 0 { "1px solid black" } else { "none" },
+                                    if split && i == active { "2px solid #0078ff" } else { "none" },
+                                )}
+                            >
+                                <DocumentView
+                                    key={document}
+                                    manager={self.layer_manager.clone()}
+                                    viewport={viewport}
+                                    assistants={self.assistants.clone()}
+                                    show_handles={self.tool == Tool::Assistant}
+                                    guide_settings={self.guide_settings}
+                                    crop={self.crop}
+                                    cursor={cursor}
+                                    viewport_change={ctx.link().callback(move |v| Msg::ViewportChanged(i, v))}
+                                    mouse_down={ctx.link().callback(move |e| Msg::MouseDown(i, e))}
+                                    mouse_move={ctx.link().callback(move |e| Msg::MouseMove(i, e))}
+                                    mouse_up={ctx.link().callback(move |e| Msg::MouseUp(i, e))}
+                                    wheel={ctx.link().callback(move |e| Msg::Wheel(i, e))}
+                                />
+                            </div>
+                        })
+                    }
                 </div>
             </div>
         }
     }
-
-    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
-        if first_render {
-            let canvas = self.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
-            self.context = Some(VirtualContext::new(canvas, 1000, 500));
-        }
-
-        self.draw();
-    }
 }
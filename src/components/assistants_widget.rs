@@ -0,0 +1,100 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::{
+    assistants::{Assistant, AssistantKind},
+    vector::Vector2,
+};
+
+pub struct AssistantsWidget {
+    kind: AssistantKind,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub assistants: Vec<Assistant>,
+    /// Where new assistants are placed.
+    pub center: Vector2,
+    #[prop_or_default]
+    pub assistants_change: Callback<Vec<Assistant>>,
+}
+
+pub enum Msg {
+    PickKind(AssistantKind),
+    Add,
+    Toggle(usize, bool),
+    Remove(usize),
+}
+
+impl Component for AssistantsWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            kind: AssistantKind::Ruler,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let mut assistants = props.assistants.clone();
+        match msg {
+            Msg::PickKind(kind) => {
+                self.kind = kind;
+                return true;
+            }
+            Msg::Add => assistants.push(Assistant::new(self.kind, props.center, 150.0)),
+            Msg::Toggle(i, enabled) => assistants[i].enabled = enabled,
+            Msg::Remove(i) => {
+                assistants.remove(i);
+            }
+        }
+        props.assistants_change.emit(assistants);
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        html! {
+            <div class="presets__container">
+                <div class="presets__list">
+                    {
+                        for props.assistants.iter().enumerate().map(|(i, assistant)| html! {
+                            <div class="presets__item">
+                                <input
+                                    type="checkbox"
+                                    checked={assistant.enabled}
+                                    onchange={ctx.link().callback(move |e: Event| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        Msg::Toggle(i, input.checked())
+                                    })}
+                                />
+                                {assistant.kind.name()}
+                                <span
+                                    class="presets__remove"
+                                    onclick={ctx.link().callback(move |_| Msg::Remove(i))}
+                                >{"×"}</span>
+                            </div>
+                        })
+                    }
+                </div>
+                <div class="presets__buttons">
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        let index: usize = select.value().parse().unwrap_or_default();
+                        Msg::PickKind(AssistantKind::ALL[index.min(AssistantKind::ALL.len() - 1)])
+                    })}>
+                        {
+                            for AssistantKind::ALL.iter().enumerate().map(|(i, &kind)| html! {
+                                <option value={i.to_string()} selected={kind == self.kind}>{kind.name()}</option>
+                            })
+                        }
+                    </select>
+                    <button onclick={ctx.link().callback(|_| Msg::Add)}>{"Add"}</button>
+                </div>
+            </div>
+        }
+    }
+}
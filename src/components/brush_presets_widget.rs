@@ -0,0 +1,161 @@
+use gloo::{
+    dialogs::{alert, prompt},
+    file::{callbacks::FileReader, File, FileReadError},
+};
+use web_sys::HtmlInputElement;
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::{
+    brush::Brush,
+    brush_presets::{BrushLibrary, BrushPreset},
+    files::download,
+};
+
+pub struct BrushPresetsWidget {
+    library: BrushLibrary,
+    selected: Option<usize>,
+    reader: Option<FileReader>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub brush: Brush,
+    #[prop_or_default]
+    pub brush_pick: Callback<Brush>,
+}
+
+pub enum Msg {
+    Pick(usize),
+    New,
+    Save,
+    Rename,
+    Delete,
+    Export,
+    Import(Option<web_sys::File>),
+    Imported(Result<String, FileReadError>),
+}
+
+impl Component for BrushPresetsWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            library: BrushLibrary::load(),
+            selected: None,
+            reader: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Pick(idx) => {
+                if let Some(preset) = self.library.get(idx) {
+                    self.selected = Some(idx);
+                    ctx.props().brush_pick.emit(preset.brush);
+                }
+                true
+            }
+            Msg::New => {
+                let default = format!("Brush {}", self.library.iter().count() + 1);
+                if let Some(name) = prompt("Preset name", Some(&default)) {
+                    let idx = self.library.add(BrushPreset::new(&name, ctx.props().brush));
+                    self.selected = Some(idx);
+                }
+                true
+            }
+            Msg::Save => {
+                if let Some(idx) = self.selected {
+                    self.library.update(idx, ctx.props().brush);
+                }
+                false
+            }
+            Msg::Rename => {
+                let preset = self.selected.and_then(|idx| self.library.get(idx));
+                if let Some(preset) = preset.filter(|p| !p.builtin) {
+                    if let Some(name) = prompt("Preset name", Some(&preset.name)) {
+                        self.library.rename(self.selected.unwrap(), name);
+                    }
+                }
+                true
+            }
+            Msg::Delete => {
+                if let Some(idx) = self.selected {
+                    self.library.remove(idx);
+                    self.selected = None;
+                }
+                true
+            }
+            Msg::Export => {
+                let json = self.library.export_json();
+                download("brushes.json", "application/json", json.as_bytes());
+                false
+            }
+            Msg::Import(file) => {
+                if let Some(file) = file {
+                    let on_load = ctx.link().callback(Msg::Imported);
+                    self.reader = Some(gloo::file::callbacks::read_as_text(
+                        &File::from(file),
+                        move |result| on_load.emit(result),
+                    ));
+                }
+                false
+            }
+            Msg::Imported(result) => {
+                self.reader = None;
+                match result {
+                    Ok(json) => {
+                        if let Err(e) = self.library.import_json(&json) {
+                            alert(&format!("Not a brush presets file: {}", e));
+                        }
+                    }
+                    Err(e) => alert(&format!("Failed to read the file: {}", e)),
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let selected = self.selected.and_then(|idx| self.library.get(idx));
+        let editable = matches!(selected, Some(p) if !p.builtin);
+        html! {
+            <div class="presets__container">
+                <div class="presets__list">
+                    {
+                        for self.library.iter().enumerate().map(|(idx, preset)| html! {
+                            <div
+                                class={if Some(idx) == self.selected { "presets__item presets__item_selected" } else { "presets__item" }}
+                                onclick={ctx.link().callback(move |_| Msg::Pick(idx))}
+                            >{&preset.name}</div>
+                        })
+                    }
+                </div>
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::New)}>{"New"}</button>
+                    <button disabled={!editable} onclick={ctx.link().callback(|_| Msg::Save)}>{"Save"}</button>
+                    <button disabled={!editable} onclick={ctx.link().callback(|_| Msg::Rename)}>{"Rename"}</button>
+                    <button disabled={!editable} onclick={ctx.link().callback(|_| Msg::Delete)}>{"Delete"}</button>
+                </div>
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::Export)}>{"Export"}</button>
+                    <label class="presets__import">
+                        {"Import"}
+                        <input
+                            type="file"
+                            accept=".json,application/json"
+                            onchange={ctx.link().callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                let file = input.files().and_then(|files| files.get(0));
+                                // Lets the same file be picked again
+                                input.set_value("");
+                                Msg::Import(file)
+                            })}
+                        />
+                    </label>
+                </div>
+            </div>
+        }
+    }
+}
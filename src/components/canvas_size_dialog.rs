@@ -0,0 +1,105 @@
+use web_sys::HtmlInputElement;
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::document::{Anchor, MAX_SIDE};
+
+pub struct CanvasSizeDialog {
+    width: u32,
+    height: u32,
+    anchor: Anchor,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Current size of the document.
+    pub width: u32,
+    pub height: u32,
+    #[prop_or_default]
+    pub resize: Callback<(u32, u32, Anchor)>,
+    #[prop_or_default]
+    pub cancel: Callback<()>,
+}
+
+pub enum Msg {
+    SetWidth(u32),
+    SetHeight(u32),
+    PickAnchor(Anchor),
+    Resize,
+    Cancel,
+}
+
+impl Component for CanvasSizeDialog {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        Self {
+            width: props.width,
+            height: props.height,
+            anchor: Anchor::CENTER,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        match msg {
+            Msg::SetWidth(width) => self.width = width.clamp(1, MAX_SIDE),
+            Msg::SetHeight(height) => self.height = height.clamp(1, MAX_SIDE),
+            Msg::PickAnchor(anchor) => self.anchor = anchor,
+            Msg::Resize => {
+                props.resize.emit((self.width, self.height, self.anchor));
+                return false;
+            }
+            Msg::Cancel => {
+                props.cancel.emit(());
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let number = |value: u32, msg: fn(u32) -> Msg| {
+            html! {
+                <input
+                    type="number"
+                    min="1"
+                    value={value.to_string()}
+                    onchange={ctx.link().callback(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        msg(input.value().parse().unwrap_or(value))
+                    })}
+                />
+            }
+        };
+        html! {
+            <div class="dialog__overlay">
+                <div class="dialog tools__settings">
+                    <b>{"Canvas size"}</b>
+                    <label>{"Width, px "}{number(self.width, Msg::SetWidth)}</label>
+                    <label>{"Height, px "}{number(self.height, Msg::SetHeight)}</label>
+                    <div>{"Anchor"}</div>
+                    <div class="anchor__grid">
+                        {
+                            for (0..9).map(|i| {
+                                let anchor = Anchor { column: i % 3, row: i / 3 };
+                                html! {
+                                    <button
+                                        class={if anchor == self.anchor { "anchor__button anchor__button_selected" } else { "anchor__button" }}
+                                        onclick={ctx.link().callback(move |_| Msg::PickAnchor(anchor))}
+                                    />
+                                }
+                            })
+                        }
+                    </div>
+                    <div class="presets__buttons">
+                        <button onclick={ctx.link().callback(|_| Msg::Resize)}>{"Resize"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::Cancel)}>{"Cancel"}</button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
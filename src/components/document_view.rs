@@ -0,0 +1,353 @@
+use web_sys::{HtmlCanvasElement, MouseEvent, WheelEvent};
+use yew::{html, Callback, Component, Context, Html, NodeRef, Properties};
+
+use crate::{
+    assistants::Assistant,
+    color::Color,
+    crop::thirds,
+    guides::{ruler_step, GuideSettings},
+    layer_manager::{Layer, Notification, RcLayerManager},
+    redraw::Redraw,
+    vector::{Rectangle, Vector2},
+    viewport::Viewport,
+    virtual_context::VirtualContext,
+};
+
+const BACKGROUND_COLOR: Color = Color::new(128, 128, 128, 255);
+
+const ASSISTANT_COLOR: Color = Color::new(0, 120, 255, 160);
+
+/// Radius of assistant handles in screen pixels.
+pub const HANDLE_RADIUS: f64 = 6.0;
+
+const GRID_COLOR: Color = Color::new(0, 0, 0, 48);
+const GUIDE_COLOR: Color = Color::new(0, 200, 255, 255);
+
+/// Grids denser than this many screen pixels aren't drawn.
+const MIN_GRID_SPACING: f64 = 4.0;
+
+const CROP_SHADE: Color = Color::new(0, 0, 0, 128);
+const CROP_COLOR: Color = Color::new(255, 255, 255, 255);
+
+/// Width of the rulers in screen pixels.
+pub const RULER_SIZE: f64 = 20.0;
+const RULER_COLOR: Color = Color::new(240, 240, 240, 255);
+const RULER_TICK_COLOR: Color = Color::new(64, 64, 64, 255);
+
+/// Minimal distance between labeled ruler ticks in screen pixels.
+const RULER_LABEL_SPACING: f64 = 60.0;
+
+/// Canvas showing the document through a viewport, repainted whenever a layer
+/// changes. Pointer events are passed on in screen coordinates of the canvas.
+pub struct DocumentView {
+    canvas_ref: NodeRef,
+    context: Option<VirtualContext>,
+    subscription: usize,
+    redraw: Redraw,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub manager: RcLayerManager,
+    /// A viewport of zero size is fit to the canvas on the first render.
+    pub viewport: Viewport,
+    #[prop_or_default]
+    pub assistants: Vec<Assistant>,
+    #[prop_or_default]
+    pub show_handles: bool,
+    #[prop_or_default]
+    pub guide_settings: GuideSettings,
+    /// Area being cropped in document coordinates.
+    #[prop_or_default]
+    pub crop: Option<Rectangle>,
+    #[prop_or("default")]
+    pub cursor: &'static str,
+    #[prop_or_default]
+    pub viewport_change: Callback<Viewport>,
+    #[prop_or_default]
+    pub mouse_down: Callback<MouseEvent>,
+    #[prop_or_default]
+    pub mouse_move: Callback<MouseEvent>,
+    #[prop_or_default]
+    pub mouse_up: Callback<MouseEvent>,
+    #[prop_or_default]
+    pub wheel: Callback<WheelEvent>,
+}
+
+pub enum Msg {
+    LayerChanged,
+    Draw,
+}
+
+impl DocumentView {
+    fn draw(&self, ctx: &Context<Self>) {
+        let context = match self.context.as_ref() {
+            Some(context) => context,
+            None => return,
+        };
+        let props = ctx.props();
+        let viewport = &props.viewport;
+        let manager = props.manager.borrow();
+        let bounds = manager.bounds();
+        let (document_min, document_max) = (bounds.coord, bounds.coord + bounds.size);
+
+        let layers = manager.iter_layers().map(Layer::grid);
+        context.composite(10.0, layers, &viewport.transform(), viewport.zoom < 1.0);
+
+        context.reset_transform();
+        let corners = [
+            document_min,
+            Vector2::new(document_max.x, document_min.y),
+            document_max,
+            Vector2::new(document_min.x, document_max.y),
+        ]
+        .map(|corner| viewport.to_screen(corner));
+        context.fill_outside(&corners, BACKGROUND_COLOR);
+
+        context.set_transform(&viewport.transform());
+        let (min, max) = viewport.visible_bounds();
+        let settings = &props.guide_settings;
+        let spacing = settings.grid_spacing;
+        if settings.grid && spacing * viewport.zoom >= MIN_GRID_SPACING {
+            // Only the lines crossing both the screen and the document
+            let (from, to) = (
+                Vector2::new(min.x.max(document_min.x), min.y.max(document_min.y)),
+                Vector2::new(max.x.min(document_max.x), max.y.min(document_max.y)),
+            );
+            let columns = ((from.x / spacing).ceil() as i64)..=((to.x / spacing).floor() as i64);
+            let rows = ((from.y / spacing).ceil() as i64)..=((to.y / spacing).floor() as i64);
+            let lines: Vec<_> = columns
+                .map(|i| {
+                    let x = i as f64 * spacing;
+                    vec![
+                        Vector2::new(x, document_min.y),
+                        Vector2::new(x, document_max.y),
+                    ]
+                })
+                .chain(rows.map(|i| {
+                    let y = i as f64 * spacing;
+                    vec![
+                        Vector2::new(document_min.x, y),
+                        Vector2::new(document_max.x, y),
+                    ]
+                }))
+                .collect();
+            context.stroke_polylines(&lines, 1.0 / viewport.zoom, GRID_COLOR);
+        }
+
+        let guides: Vec<_> = manager
+            .guides()
+            .iter()
+            .map(|guide| guide.segment(min, max).to_vec())
+            .collect();
+        context.stroke_polylines(&guides, 1.0 / viewport.zoom, GUIDE_COLOR);
+
+        let enabled = props
+            .assistants
+            .iter()
+            .filter(|assistant| assistant.enabled);
+        let overlay: Vec<_> = enabled
+            .clone()
+            .flat_map(|assistant| assistant.overlay(2000.0))
+            .collect();
+        context.stroke_polylines(&overlay, 1.0 / viewport.zoom, ASSISTANT_COLOR);
+        if props.show_handles {
+            let handles: Vec<_> = enabled
+                .flat_map(|assistant| assistant.handles.iter().copied())
+                .collect();
+            context.fill_circles(&handles, HANDLE_RADIUS / viewport.zoom, ASSISTANT_COLOR);
+        }
+        context.reset_transform();
+
+        if let Some(crop) = props.crop {
+            let (min, max) = (crop.coord, crop.coord + crop.size);
+            let corners = [
+                min,
+                Vector2::new(max.x, min.y),
+                max,
+                Vector2::new(min.x, max.y),
+            ]
+            .map(|corner| viewport.to_screen(corner));
+            context.fill_outside(&corners, CROP_SHADE);
+            let mut lines: Vec<_> = thirds(crop)
+                .into_iter()
+                .map(|line| line.into_iter().map(|p| viewport.to_screen(p)).collect())
+                .collect();
+            lines.push(corners.iter().chain(&corners[..1]).copied().collect());
+            context.stroke_polylines(&lines, 1.0, CROP_COLOR);
+        }
+
+        if settings.rulers {
+            draw_rulers(context, viewport);
+        }
+    }
+
+    /// Matches the canvas resolution to its size on the page.
+    fn fit_canvas(&mut self, ctx: &Context<Self>) {
+        let canvas = self.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
+        let (width, height) = (canvas.client_width() as u32, canvas.client_height() as u32);
+        match self.context.as_mut() {
+            Some(context) if context.width() == width && context.height() == height => return,
+            Some(context) => context.set_size(width, height),
+            None => self.context = Some(VirtualContext::new(canvas, width, height)),
+        }
+
+        let props = ctx.props();
+        let mut viewport = props.viewport;
+        let size = Vector2::new(width as f64, height as f64);
+        if viewport.size == Vector2::new(0.0, 0.0) {
+            viewport.set_size(size);
+            let manager = props.manager.borrow();
+            let document = manager.bounds();
+            if document.size.x > size.x || document.size.y > size.y {
+                viewport.fit(document);
+            } else {
+                viewport.actual_size(document);
+            }
+        } else {
+            viewport.set_size(size);
+        }
+        props.viewport_change.emit(viewport);
+    }
+}
+
+/// Rulers along the top and left edges labeled with the document coordinates met
+/// along them, one of them stays blank while the view is rotated by a right angle.
+fn draw_rulers(context: &VirtualContext, viewport: &Viewport) {
+    let size = viewport.size;
+    context.fill_rect(Rectangle::new(0.0, 0.0, size.x, RULER_SIZE), RULER_COLOR);
+    context.fill_rect(
+        Rectangle::new(0.0, RULER_SIZE, RULER_SIZE, size.y - RULER_SIZE),
+        RULER_COLOR,
+    );
+
+    let origin = viewport.to_document(Vector2::new(0.0, 0.0));
+    let along_x = viewport.to_document(Vector2::new(1.0, 0.0)) - origin;
+    let along_y = viewport.to_document(Vector2::new(0.0, 1.0)) - origin;
+
+    let mut lines = vec![];
+    let mut labels = vec![];
+    for (x, value, major) in ruler_ticks(origin.x, along_x.x, size.x) {
+        let length = if major { RULER_SIZE } else { RULER_SIZE / 4.0 };
+        lines.push(vec![
+            Vector2::new(x, RULER_SIZE),
+            Vector2::new(x, RULER_SIZE - length),
+        ]);
+        if major {
+            labels.push((format_tick(value), Vector2::new(x + 2.0, 9.0)));
+        }
+    }
+    for (y, value, major) in ruler_ticks(origin.y, along_y.y, size.y) {
+        if y < RULER_SIZE {
+            continue;
+        }
+        let length = if major { RULER_SIZE } else { RULER_SIZE / 4.0 };
+        lines.push(vec![
+            Vector2::new(RULER_SIZE, y),
+            Vector2::new(RULER_SIZE - length, y),
+        ]);
+        if major {
+            labels.push((format_tick(value), Vector2::new(2.0, y + 10.0)));
+        }
+    }
+    context.stroke_polylines(&lines, 1.0, RULER_TICK_COLOR);
+    context.fill_labels(&labels, RULER_TICK_COLOR);
+}
+
+/// Screen offsets, document values and whether the tick is labeled for a ruler
+/// `length` pixels long, starting at document value `start` that changes by
+/// `per_pixel` each screen pixel.
+fn ruler_ticks(start: f64, per_pixel: f64, length: f64) -> Vec<(f64, f64, bool)> {
+    if per_pixel.abs() < 1e-9 {
+        return vec![];
+    }
+    let minor = ruler_step(RULER_LABEL_SPACING * per_pixel.abs()) / 5.0;
+    let end = start + per_pixel * length;
+    let first = (start.min(end) / minor).ceil() as i64;
+    let last = (start.max(end) / minor).floor() as i64;
+    (first..=last)
+        .map(|i| {
+            let value = i as f64 * minor;
+            ((value - start) / per_pixel, value, i % 5 == 0)
+        })
+        .collect()
+}
+
+fn format_tick(value: f64) -> String {
+    // Steps are 1, 2 or 5 times a power of ten, hide float noise of small ones
+    let rounded = (value * 1000.0).round() / 1000.0;
+    format!("{}", rounded)
+}
+
+impl Component for DocumentView {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let on_notification = ctx.link().callback(|m| m);
+
+        let subscription = ctx
+            .props()
+            .manager
+            .borrow_mut()
+            .subscribe(Box::new(move |n| {
+                on_notification.emit(match n {
+                    Notification::Change { .. } => Msg::LayerChanged,
+                });
+            }));
+
+        Self {
+            canvas_ref: NodeRef::default(),
+            context: None,
+            subscription,
+            redraw: Redraw::default(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::LayerChanged => self.redraw.request(ctx.link(), Msg::Draw),
+            Msg::Draw => {
+                self.redraw.done();
+                self.draw(ctx);
+            }
+        }
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        html! {
+            <canvas
+                style={format!("
+                    display: block;
+                    width: 100%;
+                    height: 100%;
+                    cursor: {};
+                ", props.cursor)}
+                ref={self.canvas_ref.clone()}
+                onmousedown={props.mouse_down.clone()}
+                onmousemove={props.mouse_move.clone()}
+                onmouseup={props.mouse_up.clone()}
+                onwheel={props.wheel.clone()}
+            />
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        // The layout may have changed the canvas size
+        self.fit_canvas(ctx);
+        // The app renders from an animation frame, drawing now keeps the view in that
+        // frame and covers any redraw still waiting for the next one
+        self.redraw.done();
+        self.draw(ctx);
+    }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        ctx.props()
+            .manager
+            .borrow_mut()
+            .unsubscribe(self.subscription);
+    }
+}
@@ -0,0 +1,123 @@
+use web_sys::HtmlInputElement;
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::guides::GuideSettings;
+
+pub struct GuidesWidget;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub settings: GuideSettings,
+    #[prop_or_default]
+    pub settings_change: Callback<GuideSettings>,
+    #[prop_or_default]
+    pub guides_clear: Callback<()>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Toggle {
+    Rulers,
+    Grid,
+    SnapToGuides,
+    SnapToGrid,
+    SnapToEdges,
+}
+
+impl Toggle {
+    const ALL: [Toggle; 5] = [
+        Toggle::Rulers,
+        Toggle::Grid,
+        Toggle::SnapToGuides,
+        Toggle::SnapToGrid,
+        Toggle::SnapToEdges,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Toggle::Rulers => "Rulers",
+            Toggle::Grid => "Grid",
+            Toggle::SnapToGuides => "Snap to guides",
+            Toggle::SnapToGrid => "Snap to grid",
+            Toggle::SnapToEdges => "Snap to layer edges",
+        }
+    }
+
+    fn field(self, settings: &mut GuideSettings) -> &mut bool {
+        match self {
+            Toggle::Rulers => &mut settings.rulers,
+            Toggle::Grid => &mut settings.grid,
+            Toggle::SnapToGuides => &mut settings.snap_to_guides,
+            Toggle::SnapToGrid => &mut settings.snap_to_grid,
+            Toggle::SnapToEdges => &mut settings.snap_to_edges,
+        }
+    }
+}
+
+pub enum Msg {
+    Toggle(Toggle, bool),
+    SetGridSpacing(f64),
+    ClearGuides,
+}
+
+impl Component for GuidesWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let mut settings = props.settings;
+        match msg {
+            Msg::Toggle(toggle, enabled) => *toggle.field(&mut settings) = enabled,
+            Msg::SetGridSpacing(spacing) => settings.grid_spacing = spacing.max(1.0),
+            Msg::ClearGuides => {
+                props.guides_clear.emit(());
+                return false;
+            }
+        }
+        props.settings_change.emit(settings);
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let mut settings = ctx.props().settings;
+        html! {
+            <div class="tools__settings">
+                {
+                    for Toggle::ALL.iter().map(|&toggle| html! {
+                        <label>
+                            <input
+                                type="checkbox"
+                                checked={*toggle.field(&mut settings)}
+                                onchange={ctx.link().callback(move |e: Event| {
+                                    let input: HtmlInputElement = e.target_unchecked_into();
+                                    Msg::Toggle(toggle, input.checked())
+                                })}
+                            />
+                            {toggle.name()}
+                        </label>
+                    })
+                }
+                <label>
+                    {"Grid spacing "}
+                    <input
+                        type="number"
+                        min="1"
+                        value={settings.grid_spacing.to_string()}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetGridSpacing(input.value().parse().unwrap_or(50.0))
+                        })}
+                    />
+                </label>
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::ClearGuides)}>{"Clear guides"}</button>
+                </div>
+            </div>
+        }
+    }
+}
@@ -1,17 +1,34 @@
+use std::collections::BTreeSet;
+
 use web_sys::HtmlCanvasElement;
 use yew::{html, Component, Context, Html, NodeRef, Properties};
 
 use crate::{
-    color::Color,
     layer_manager::{Notification, RcLayerManager},
-    vector::Rectangle,
+    redraw::Redraw,
+    vector::{Rectangle, Transform, Vector2},
     virtual_context::VirtualContext,
 };
 
+/// Box the layer thumbnails are fit into.
+const THUMBNAIL_WIDTH: f64 = 200.0;
+const THUMBNAIL_HEIGHT: f64 = 100.0;
+
+/// Thumbnails lag behind painting by up to this long.
+const THUMBNAIL_DELAY_MILLIS: u32 = 250;
+
 pub struct LayersWidget {
     canvas_refs: Vec<NodeRef>,
     contexts: Vec<VirtualContext>,
     manager: RcLayerManager,
+    thumbnail_size: Vector2,
+    /// Part of the document the thumbnails show, the layers of infinite documents may
+    /// grow past it.
+    frame: Rectangle,
+    /// Layers changed since the thumbnails were last drawn.
+    changed: BTreeSet<usize>,
+    redraw: Redraw,
+    subscription: usize,
 }
 
 #[derive(Properties, PartialEq)]
@@ -20,7 +37,8 @@ pub struct Props {
 }
 
 pub enum Msg {
-    LayerChanged,
+    LayerChanged(usize),
+    Draw,
     PickLayer(usize),
 }
 
@@ -34,13 +52,25 @@ impl Component for LayersWidget {
 
         let on_notifiication = ctx.link().callback(|m| m);
 
-        manager.borrow_mut().subscribe(Box::new(move |n| {
+        let subscription = manager.borrow_mut().subscribe(Box::new(move |n| {
             on_notifiication.emit(match n {
-                Notification::Change { .. } => Msg::LayerChanged,
+                Notification::Change { id } => Msg::LayerChanged(id),
             });
         }));
 
+        let frame = manager.borrow().bounds();
+        let thumbnail_size = {
+            let (width, height) = (frame.size.x, frame.size.y);
+            let scale = (THUMBNAIL_WIDTH / width).min(THUMBNAIL_HEIGHT / height);
+            Vector2::new(
+                (width * scale).round().max(1.0),
+                (height * scale).round().max(1.0),
+            )
+        };
+
         Self {
+            thumbnail_size,
+            frame,
             canvas_refs: manager
                 .borrow()
                 .iter_layers()
@@ -48,18 +78,31 @@ impl Component for LayersWidget {
                 .collect(),
             manager: manager.clone(),
             contexts: vec![],
+            changed: BTreeSet::new(),
+            redraw: Redraw::throttled(THUMBNAIL_DELAY_MILLIS),
+            subscription,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::LayerChanged => {
+            Msg::LayerChanged(id) => {
+                self.changed.insert(id);
+                self.redraw.request(ctx.link(), Msg::Draw);
+                false
+            }
+            Msg::Draw => {
+                self.redraw.done();
                 let manager = self.manager.borrow();
-                for (idx, ctx) in self.contexts.iter().enumerate() {
-                    ctx.draw_image_bounded(
-                        manager.get_layer(idx).unwrap().get_canvas(),
-                        Rectangle::new(0.0, 0.0, 200.0, 100.0),
-                    );
+                let scale = self.thumbnail_size.x / self.frame.size.x;
+                let transform = Transform::translate(self.frame.coord * -1.0)
+                    .then(Transform::scale(scale, scale));
+                for idx in std::mem::take(&mut self.changed) {
+                    if let (Some(ctx), Some(layer)) =
+                        (self.contexts.get(idx), manager.get_layer(idx))
+                    {
+                        ctx.composite(5.0, [layer.grid()], &transform, true);
+                    }
                 }
                 false
             }
@@ -81,8 +124,8 @@ impl Component for LayersWidget {
                                 <canvas
                                     class="layers__one-layer-canvas"
                                     style={if layer.get_selected() { "outline: 2px solid blue; border: 1px solid white" } else {""}}
-                                    width=200
-                                    height=100
+                                    width={self.thumbnail_size.x.to_string()}
+                                    height={self.thumbnail_size.y.to_string()}
                                     key={id}
                                     onmouseup={ctx.link().callback(move |_| Msg::PickLayer(id))}
                                     ref={self.canvas_refs[id].clone()}
@@ -95,17 +138,25 @@ impl Component for LayersWidget {
         }
     }
 
-    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
         if first_render {
             for (_i, canvas_ref) in self.canvas_refs.iter().enumerate() {
                 let canvas = canvas_ref.cast::<HtmlCanvasElement>().unwrap();
 
-                let context = VirtualContext::new(canvas, 200, 100);
-
-                context.checkerboard(5.0, Color::new(191, 191, 191, 255), Color::WHITE);
-
-                self.contexts.push(context);
+                let (width, height) = (self.thumbnail_size.x as u32, self.thumbnail_size.y as u32);
+                self.contexts
+                    .push(VirtualContext::new(canvas, width, height));
             }
+            // Draws the checkerboard and whatever the layers hold, e.g. the background
+            self.changed.extend(0..self.contexts.len());
+            ctx.link().send_message(Msg::Draw);
         }
     }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        ctx.props()
+            .manager
+            .borrow_mut()
+            .unsubscribe(self.subscription);
+    }
 }
@@ -1,5 +1,17 @@
 pub mod app;
+pub mod assistants_widget;
+pub mod brush_presets_widget;
+pub mod canvas_size_dialog;
 pub mod color_circle;
 pub mod color_picker;
+pub mod document_view;
 pub mod draganddrop_container;
+pub mod guides_widget;
 pub mod layers_widget;
+pub mod navigator_widget;
+pub mod new_document_dialog;
+pub mod resources_widget;
+pub mod scale_image_dialog;
+pub mod tools_widget;
+pub mod transform_widget;
+pub mod view_widget;
@@ -0,0 +1,216 @@
+use web_sys::{HtmlCanvasElement, HtmlInputElement, MouseEvent};
+use yew::{html, Callback, Component, Context, Html, InputEvent, NodeRef, Properties, TargetCast};
+
+use crate::{
+    color::Color,
+    layer_manager::{Layer, Notification, RcLayerManager},
+    redraw::Redraw,
+    vector::{Rectangle, Transform, Vector2},
+    viewport::{Viewport, MAX_ZOOM, MIN_ZOOM},
+    virtual_context::VirtualContext,
+};
+
+const WIDTH: u32 = 200;
+const HEIGHT: u32 = 150;
+
+const BACKGROUND_COLOR: Color = Color::new(128, 128, 128, 255);
+const VIEW_COLOR: Color = Color::new(255, 0, 0, 255);
+
+pub struct NavigatorWidget {
+    canvas_ref: NodeRef,
+    context: Option<VirtualContext>,
+    dragging: bool,
+    redraw: Redraw,
+    subscription: usize,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub manager: RcLayerManager,
+    pub viewport: Viewport,
+    #[prop_or_default]
+    pub viewport_change: Callback<Viewport>,
+}
+
+pub enum Msg {
+    LayerChanged,
+    Draw,
+    MouseDown(MouseEvent),
+    MouseMove(MouseEvent),
+    MouseUp,
+    /// Zoom as a power of two.
+    Zoom(f64),
+}
+
+impl NavigatorWidget {
+    /// Maps document coordinates to the thumbnail, fitting the document in the middle.
+    fn thumbnail_transform(ctx: &Context<Self>) -> Transform {
+        let document = document_bounds(ctx);
+        let size = document.size;
+        let scale = (WIDTH as f64 / size.x).min(HEIGHT as f64 / size.y);
+        let offset = (Vector2::new(WIDTH as f64, HEIGHT as f64) - size * scale) * 0.5;
+        Transform::translate(document.coord * -1.0)
+            .then(Transform::scale(scale, scale))
+            .then(Transform::translate(offset))
+    }
+
+    fn draw(&self, ctx: &Context<Self>) {
+        let context = match self.context.as_ref() {
+            Some(context) => context,
+            None => return,
+        };
+        let props = ctx.props();
+        let thumbnail = Self::thumbnail_transform(ctx);
+        let document = document_bounds(ctx);
+        let (min, max) = (document.coord, document.coord + document.size);
+        let corners = [
+            min,
+            Vector2::new(max.x, min.y),
+            max,
+            Vector2::new(min.x, max.y),
+        ];
+
+        let manager = props.manager.borrow();
+        context.composite(
+            5.0,
+            manager.iter_layers().map(Layer::grid),
+            &thumbnail,
+            true,
+        );
+        context.reset_transform();
+        context.fill_outside(&corners.map(|p| thumbnail.apply(p)), BACKGROUND_COLOR);
+
+        let viewport = props.viewport;
+        let screen = viewport.size;
+        let visible: Vec<_> = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(screen.x, 0.0),
+            screen,
+            Vector2::new(0.0, screen.y),
+            Vector2::new(0.0, 0.0),
+        ]
+        .iter()
+        .map(|&p| thumbnail.apply(viewport.to_document(p)))
+        .collect();
+        context.stroke_polylines(&[visible], 1.0, VIEW_COLOR);
+    }
+
+    /// Centers the view on the document point under the pointer.
+    fn move_view(&self, ctx: &Context<Self>, e: &MouseEvent) {
+        let inverse = Self::thumbnail_transform(ctx)
+            .inverse()
+            .unwrap_or(Transform::IDENTITY);
+        let point = Vector2::new(e.offset_x() as f64, e.offset_y() as f64);
+        let mut viewport = ctx.props().viewport;
+        viewport.center = inverse.apply(point);
+        ctx.props().viewport_change.emit(viewport);
+    }
+}
+
+fn document_bounds(ctx: &Context<NavigatorWidget>) -> Rectangle {
+    ctx.props().manager.borrow().bounds()
+}
+
+impl Component for NavigatorWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let on_notification = ctx.link().callback(|m| m);
+
+        let subscription = ctx
+            .props()
+            .manager
+            .borrow_mut()
+            .subscribe(Box::new(move |n| {
+                on_notification.emit(match n {
+                    Notification::Change { .. } => Msg::LayerChanged,
+                });
+            }));
+
+        Self {
+            canvas_ref: NodeRef::default(),
+            context: None,
+            dragging: false,
+            redraw: Redraw::default(),
+            subscription,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::LayerChanged => self.redraw.request(ctx.link(), Msg::Draw),
+            Msg::Draw => {
+                self.redraw.done();
+                self.draw(ctx);
+            }
+            Msg::MouseDown(e) => {
+                self.dragging = true;
+                self.move_view(ctx, &e);
+            }
+            Msg::MouseMove(e) => {
+                if self.dragging {
+                    self.move_view(ctx, &e);
+                }
+            }
+            Msg::MouseUp => self.dragging = false,
+            Msg::Zoom(power) => {
+                let mut viewport = ctx.props().viewport;
+                viewport.set_zoom(power.exp2());
+                ctx.props().viewport_change.emit(viewport);
+            }
+        }
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let zoom = ctx.props().viewport.zoom;
+        html! {
+            <div class="navigator__container">
+                <canvas
+                    class="navigator__canvas"
+                    width={WIDTH.to_string()}
+                    height={HEIGHT.to_string()}
+                    ref={self.canvas_ref.clone()}
+                    onmousedown={ctx.link().callback(Msg::MouseDown)}
+                    onmousemove={ctx.link().callback(Msg::MouseMove)}
+                    onmouseup={ctx.link().callback(|_| Msg::MouseUp)}
+                    onmouseleave={ctx.link().callback(|_| Msg::MouseUp)}
+                />
+                <label>
+                    {format!("Zoom {:.0}%", zoom * 100.0)}
+                    <input
+                        type="range"
+                        min={MIN_ZOOM.log2().to_string()}
+                        max={MAX_ZOOM.log2().to_string()}
+                        step="0.01"
+                        value={zoom.log2().to_string()}
+                        oninput={ctx.link().callback(|e: InputEvent| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::Zoom(input.value().parse().unwrap_or_default())
+                        })}
+                    />
+                </label>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            let canvas = self.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
+            self.context = Some(VirtualContext::new(canvas, WIDTH, HEIGHT));
+        }
+
+        // Rendered from the app's animation frame, so draw in it
+        self.redraw.done();
+        self.draw(ctx);
+    }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        ctx.props()
+            .manager
+            .borrow_mut()
+            .unsubscribe(self.subscription);
+    }
+}
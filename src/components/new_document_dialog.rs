@@ -0,0 +1,204 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::{
+    color::Color,
+    document::{Background, DocumentSettings, MAX_SIDE, PRESETS},
+};
+
+pub struct NewDocumentDialog {
+    settings: DocumentSettings,
+    /// Index of the preset the settings match, `None` once edited.
+    preset: Option<usize>,
+    /// Color picked for a custom background, kept while another background is picked.
+    custom_color: Color,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Settings the dialog starts with.
+    pub settings: DocumentSettings,
+    #[prop_or_default]
+    pub create: Callback<DocumentSettings>,
+    #[prop_or_default]
+    pub cancel: Callback<()>,
+}
+
+pub enum Msg {
+    PickPreset(usize),
+    SetWidth(u32),
+    SetHeight(u32),
+    SetResolution(u32),
+    /// Index into `BACKGROUNDS`.
+    PickBackground(usize),
+    SetCustomColor(Color),
+    SetInfinite(bool),
+    Create,
+    Cancel,
+}
+
+const BACKGROUNDS: [&str; 3] = ["White", "Transparent", "Custom color"];
+
+fn background_index(background: Background) -> usize {
+    match background {
+        Background::White => 0,
+        Background::Transparent => 1,
+        Background::Custom(_) => 2,
+    }
+}
+
+impl Component for NewDocumentDialog {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let settings = ctx.props().settings;
+        let custom_color = match settings.background {
+            Background::Custom(color) => color,
+            _ => Color::WHITE,
+        };
+        Self {
+            settings,
+            preset: None,
+            custom_color,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let settings = &mut self.settings;
+        match msg {
+            Msg::PickPreset(i) => {
+                self.preset = PRESETS.get(i).map(|preset| {
+                    *settings = preset.apply(*settings);
+                    i
+                });
+            }
+            Msg::SetWidth(width) => {
+                settings.width = width.clamp(1, MAX_SIDE);
+                self.preset = None;
+            }
+            Msg::SetHeight(height) => {
+                settings.height = height.clamp(1, MAX_SIDE);
+                self.preset = None;
+            }
+            Msg::SetResolution(resolution) => {
+                settings.resolution = resolution.max(1);
+                self.preset = None;
+            }
+            Msg::PickBackground(i) => {
+                settings.background = match i {
+                    0 => Background::White,
+                    1 => Background::Transparent,
+                    _ => Background::Custom(self.custom_color),
+                }
+            }
+            Msg::SetCustomColor(color) => {
+                self.custom_color = color;
+                settings.background = Background::Custom(color);
+            }
+            Msg::SetInfinite(infinite) => settings.infinite = infinite,
+            Msg::Create => {
+                ctx.props().create.emit(*settings);
+                return false;
+            }
+            Msg::Cancel => {
+                ctx.props().cancel.emit(());
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let settings = self.settings;
+        let (print_width, print_height) = settings.print_size();
+        let number = |value: u32, msg: fn(u32) -> Msg| {
+            html! {
+                <input
+                    type="number"
+                    min="1"
+                    value={value.to_string()}
+                    onchange={ctx.link().callback(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        msg(input.value().parse().unwrap_or(value))
+                    })}
+                />
+            }
+        };
+        html! {
+            <div class="dialog__overlay">
+                <div class="dialog tools__settings">
+                    <b>{"New document"}</b>
+                    <label>
+                        {"Preset "}
+                        <select onchange={ctx.link().callback(|e: Event| {
+                            let select: HtmlSelectElement = e.target_unchecked_into();
+                            Msg::PickPreset(select.value().parse().unwrap_or(usize::MAX))
+                        })}>
+                            <option value="" selected={self.preset.is_none()}>{"Custom"}</option>
+                            {
+                                for PRESETS.iter().enumerate().map(|(i, preset)| html! {
+                                    <option
+                                        value={i.to_string()}
+                                        selected={self.preset == Some(i)}
+                                    >{preset.name}</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <label>{"Width, px "}{number(settings.width, Msg::SetWidth)}</label>
+                    <label>{"Height, px "}{number(settings.height, Msg::SetHeight)}</label>
+                    <label>{"Resolution, dpi "}{number(settings.resolution, Msg::SetResolution)}</label>
+                    <div>{format!("Print size {:.2} × {:.2} in", print_width, print_height)}</div>
+                    <label>
+                        {"Background "}
+                        <select onchange={ctx.link().callback(|e: Event| {
+                            let select: HtmlSelectElement = e.target_unchecked_into();
+                            Msg::PickBackground(select.value().parse().unwrap_or_default())
+                        })}>
+                            {
+                                for BACKGROUNDS.iter().enumerate().map(|(i, name)| html! {
+                                    <option
+                                        value={i.to_string()}
+                                        selected={i == background_index(settings.background)}
+                                    >{name}</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    {
+                        match settings.background {
+                            Background::Custom(color) => html! {
+                                <input
+                                    type="color"
+                                    value={color.to_hex()}
+                                    onchange={ctx.link().batch_callback(|e: Event| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        Color::from_hex(&input.value()).map(Msg::SetCustomColor)
+                                    })}
+                                />
+                            },
+                            _ => html! {},
+                        }
+                    }
+                    <label title="Layers grow as they're painted past the edges">
+                        <input
+                            type="checkbox"
+                            checked={settings.infinite}
+                            onchange={ctx.link().callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                Msg::SetInfinite(input.checked())
+                            })}
+                        />
+                        {"Infinite canvas"}
+                    </label>
+                    <div class="presets__buttons">
+                        <button onclick={ctx.link().callback(|_| Msg::Create)}>{"Create"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::Cancel)}>{"Cancel"}</button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
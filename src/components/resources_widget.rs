@@ -0,0 +1,439 @@
+use gloo::{
+    dialogs::{alert, prompt},
+    file::{callbacks::FileReader, File, FileReadError},
+    storage::errors::StorageError,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, InputEvent, Properties, TargetCast};
+
+use crate::{
+    brush::Brush,
+    files::ImageLoader,
+    gimp_formats::{parse_gbr, parse_pat},
+    grain::{Grain, GrainSource, Paper},
+    layer_manager::RcLayerManager,
+    raster::RgbaImage,
+    resources::RcResources,
+};
+
+pub struct ResourcesWidget {
+    reader: Option<FileReader>,
+    image_loader: Option<ImageLoader>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub manager: RcLayerManager,
+    pub resources: RcResources,
+    pub brush: Brush,
+    #[prop_or_default]
+    pub brush_change: Callback<Brush>,
+}
+
+pub enum Msg {
+    CaptureTip,
+    CapturePattern,
+    SetTip(Option<u32>),
+    SetPattern(Option<u32>),
+    Remove(u32),
+    Import(Option<web_sys::File>),
+    Imported(String, Result<Vec<u8>, FileReadError>),
+    SetGrain(Option<GrainSource>),
+    SetGrainScale(f64),
+    SetGrainContrast(f64),
+    SetGrainInvert(bool),
+    ImportGrain(Option<web_sys::File>),
+    GrainImported(String, Option<RgbaImage>),
+}
+
+impl Component for ResourcesWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            reader: None,
+            image_loader: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let mut brush = props.brush;
+        match msg {
+            Msg::CaptureTip => {
+                if let Some(image) = capture_selected_layer(props) {
+                    if let Some(name) = prompt("Brush tip name", Some("Captured tip")) {
+                        match props.resources.borrow_mut().add_tip(name, image.to_mask()) {
+                            Ok(id) => brush.tip = Some(id),
+                            Err(e) => alert_not_saved(e),
+                        }
+                    }
+                }
+            }
+            Msg::CapturePattern => {
+                if let Some(image) = capture_selected_layer(props) {
+                    if let Some(name) = prompt("Pattern name", Some("Captured pattern")) {
+                        match props.resources.borrow_mut().add_pattern(name, image) {
+                            Ok(id) => brush.pattern = Some(id),
+                            Err(e) => alert_not_saved(e),
+                        }
+                    }
+                }
+            }
+            Msg::SetTip(tip) => brush.tip = tip,
+            Msg::SetPattern(pattern) => brush.pattern = pattern,
+            Msg::Remove(id) => {
+                props.resources.borrow_mut().remove(id);
+                if brush.tip == Some(id) {
+                    brush.tip = None;
+                }
+                if brush.pattern == Some(id) {
+                    brush.pattern = None;
+                }
+                if matches!(brush.grain, Some(grain) if grain.source == GrainSource::Imported(id)) {
+                    brush.grain = None;
+                }
+            }
+            Msg::Import(file) => {
+                if let Some(file) = file {
+                    let name = file.name();
+                    let on_load = ctx.link().callback(move |r| Msg::Imported(name.clone(), r));
+                    self.reader = Some(gloo::file::callbacks::read_as_bytes(
+                        &File::from(file),
+                        move |result| on_load.emit(result),
+                    ));
+                }
+                return false;
+            }
+            Msg::Imported(file_name, result) => {
+                self.reader = None;
+                let bytes = match result {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        alert(&format!("Failed to read the file: {}", e));
+                        return false;
+                    }
+                };
+                let mut resources = props.resources.borrow_mut();
+                let imported = if file_name.to_lowercase().ends_with(".pat") {
+                    parse_pat(&bytes).map(|(name, image)| {
+                        resources
+                            .add_pattern(or_file_name(name, &file_name), image)
+                            .map(|id| brush.pattern = Some(id))
+                    })
+                } else {
+                    parse_gbr(&bytes).map(|(name, mask)| {
+                        resources
+                            .add_tip(or_file_name(name, &file_name), mask)
+                            .map(|id| brush.tip = Some(id))
+                    })
+                };
+                match imported {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => alert_not_saved(e),
+                    Err(e) => alert(&format!("Failed to import {}: {}", file_name, e)),
+                }
+            }
+            Msg::SetGrain(source) => {
+                brush.grain = source.map(|source| match brush.grain {
+                    Some(grain) => Grain { source, ..grain },
+                    None => Grain::new(source),
+                })
+            }
+            Msg::SetGrainScale(scale) => {
+                if let Some(grain) = brush.grain.as_mut() {
+                    grain.scale = scale;
+                }
+            }
+            Msg::SetGrainContrast(contrast) => {
+                if let Some(grain) = brush.grain.as_mut() {
+                    grain.contrast = contrast;
+                }
+            }
+            Msg::SetGrainInvert(invert) => {
+                if let Some(grain) = brush.grain.as_mut() {
+                    grain.invert = invert;
+                }
+            }
+            Msg::ImportGrain(file) => {
+                if let Some(file) = file {
+                    let name = file.name();
+                    let on_load = ctx
+                        .link()
+                        .callback(move |image| Msg::GrainImported(name.clone(), image));
+                    self.image_loader =
+                        Some(ImageLoader::new(&file, move |image| on_load.emit(image)));
+                }
+                return false;
+            }
+            Msg::GrainImported(file_name, image) => {
+                self.image_loader = None;
+                match image {
+                    Some(image) => {
+                        let added = props
+                            .resources
+                            .borrow_mut()
+                            .add_grain(file_name, image.to_luminance());
+                        let id = match added {
+                            Ok(id) => id,
+                            Err(e) => {
+                                alert_not_saved(e);
+                                return false;
+                            }
+                        };
+                        let source = GrainSource::Imported(id);
+                        brush.grain = Some(match brush.grain {
+                            Some(grain) => Grain { source, ..grain },
+                            None => Grain::new(source),
+                        });
+                    }
+                    None => {
+                        alert(&format!("Failed to import {}: not an image", file_name));
+                        return false;
+                    }
+                }
+            }
+        }
+        if brush != props.brush {
+            props.brush_change.emit(brush);
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let resources = props.resources.borrow();
+        html! {
+            <div class="presets__container">
+                <div class="presets__list">
+                    { self.view_item(ctx, "Round", props.brush.tip.is_none(), || Msg::SetTip(None), None) }
+                    {
+                        for resources.iter_tips().map(|tip| {
+                            let id = tip.id;
+                            self.view_item(
+                                ctx,
+                                &tip.name,
+                                props.brush.tip == Some(id),
+                                move || Msg::SetTip(Some(id)),
+                                Some(id),
+                            )
+                        })
+                    }
+                </div>
+                <div class="presets__list">
+                    { self.view_item(ctx, "Color", props.brush.pattern.is_none(), || Msg::SetPattern(None), None) }
+                    {
+                        for resources.iter_patterns().map(|pattern| {
+                            let id = pattern.id;
+                            self.view_item(
+                                ctx,
+                                &pattern.name,
+                                props.brush.pattern == Some(id),
+                                move || Msg::SetPattern(Some(id)),
+                                Some(id),
+                            )
+                        })
+                    }
+                </div>
+                { self.view_grain(ctx) }
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::CaptureTip)}>{"Tip from layer"}</button>
+                    <button onclick={ctx.link().callback(|_| Msg::CapturePattern)}>{"Pattern from layer"}</button>
+                </div>
+                <div class="presets__buttons">
+                    <label class="presets__import">
+                        {"Import .gbr / .pat"}
+                        <input
+                            type="file"
+                            accept=".gbr,.pat"
+                            onchange={ctx.link().callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                let file = input.files().and_then(|files| files.get(0));
+                                // Lets the same file be picked again
+                                input.set_value("");
+                                Msg::Import(file)
+                            })}
+                        />
+                    </label>
+                    <label class="presets__import">
+                        {"Import grain image"}
+                        <input
+                            type="file"
+                            accept="image/*"
+                            onchange={ctx.link().callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                let file = input.files().and_then(|files| files.get(0));
+                                // Lets the same file be picked again
+                                input.set_value("");
+                                Msg::ImportGrain(file)
+                            })}
+                        />
+                    </label>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl ResourcesWidget {
+    fn view_grain(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let grain = props.brush.grain;
+        let source = grain.map(|grain| grain.source);
+        let resources = props.resources.borrow();
+        html! {
+            <div class="tools__settings">
+                <label>
+                    {"Grain "}
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        Msg::SetGrain(parse_grain_source(&select.value()))
+                    })}>
+                        <option value="none" selected={source.is_none()}>{"None"}</option>
+                        {
+                            for Paper::ALL.iter().enumerate().map(|(i, &paper)| html! {
+                                <option
+                                    value={format!("paper:{}", i)}
+                                    selected={source == Some(GrainSource::Paper(paper))}
+                                >{paper.name()}</option>
+                            })
+                        }
+                        {
+                            for resources.iter_grains().map(|grain| html! {
+                                <option
+                                    value={format!("imported:{}", grain.id)}
+                                    selected={source == Some(GrainSource::Imported(grain.id))}
+                                >{&grain.name}</option>
+                            })
+                        }
+                    </select>
+                </label>
+                {
+                    match grain {
+                        Some(grain) => html! {
+                            <>
+                                <label>
+                                    {"Scale "}
+                                    <input
+                                        type="range"
+                                        min="10"
+                                        max="400"
+                                        value={((grain.scale * 100.0) as i32).to_string()}
+                                        oninput={ctx.link().callback(|e: InputEvent| {
+                                            let input: HtmlInputElement = e.target_unchecked_into();
+                                            Msg::SetGrainScale(input.value_as_number() / 100.0)
+                                        })}
+                                    />
+                                </label>
+                                <label>
+                                    {"Contrast "}
+                                    <input
+                                        type="range"
+                                        min="0"
+                                        max="400"
+                                        value={((grain.contrast * 100.0) as i32).to_string()}
+                                        oninput={ctx.link().callback(|e: InputEvent| {
+                                            let input: HtmlInputElement = e.target_unchecked_into();
+                                            Msg::SetGrainContrast(input.value_as_number() / 100.0)
+                                        })}
+                                    />
+                                </label>
+                                <label>
+                                    {"Invert "}
+                                    <input
+                                        type="checkbox"
+                                        checked={grain.invert}
+                                        onchange={ctx.link().callback(|e: Event| {
+                                            let input: HtmlInputElement = e.target_unchecked_into();
+                                            Msg::SetGrainInvert(input.checked())
+                                        })}
+                                    />
+                                </label>
+                            </>
+                        },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_item(
+        &self,
+        ctx: &Context<Self>,
+        name: &str,
+        selected: bool,
+        pick: impl Fn() -> Msg + 'static,
+        removable: Option<u32>,
+    ) -> Html {
+        html! {
+            <div
+                class={if selected { "presets__item presets__item_selected" } else { "presets__item" }}
+                onclick={ctx.link().callback(move |_| pick())}
+            >
+                {name}
+                {
+                    match removable {
+                        Some(id) => html! {
+                            <span
+                                class="presets__remove"
+                                onclick={ctx.link().callback(move |e: web_sys::MouseEvent| {
+                                    e.stop_propagation();
+                                    Msg::Remove(id)
+                                })}
+                            >{"×"}</span>
+                        },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+/// Painted part of the selected layer.
+fn capture_selected_layer(props: &Props) -> Option<RgbaImage> {
+    let image = match props.manager.borrow().selected_image() {
+        Some(image) => image,
+        None => {
+            alert("Select a layer to capture");
+            return None;
+        }
+    };
+    match image.opaque_bounds() {
+        Some((x, y, width, height)) => Some(image.crop(x, y, width, height)),
+        None => {
+            alert("The selected layer is empty");
+            None
+        }
+    }
+}
+
+fn parse_grain_source(value: &str) -> Option<GrainSource> {
+    let (kind, index) = value.split_once(':')?;
+    let index: u32 = index.parse().ok()?;
+    match kind {
+        "paper" => Paper::ALL
+            .get(index as usize)
+            .map(|&paper| GrainSource::Paper(paper)),
+        "imported" => Some(GrainSource::Imported(index)),
+        _ => None,
+    }
+}
+
+fn alert_not_saved(e: StorageError) {
+    alert(&format!(
+        "Not enough browser storage to keep it, remove some brush resources first ({})",
+        e
+    ));
+}
+
+fn or_file_name(name: String, file_name: &str) -> String {
+    if name.is_empty() {
+        file_name.to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,134 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::{document::MAX_SIDE, resample::Filter};
+
+pub struct ScaleImageDialog {
+    width: u32,
+    height: u32,
+    keep_aspect: bool,
+    filter: Filter,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    /// Current size of the document.
+    pub width: u32,
+    pub height: u32,
+    #[prop_or_default]
+    pub scale: Callback<(u32, u32, Filter)>,
+    #[prop_or_default]
+    pub cancel: Callback<()>,
+}
+
+pub enum Msg {
+    SetWidth(u32),
+    SetHeight(u32),
+    SetKeepAspect(bool),
+    PickFilter(Filter),
+    Scale,
+    Cancel,
+}
+
+impl Component for ScaleImageDialog {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        Self {
+            width: props.width,
+            height: props.height,
+            keep_aspect: true,
+            filter: Filter::Bicubic,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let aspect = props.width as f64 / props.height as f64;
+        match msg {
+            Msg::SetWidth(width) => {
+                self.width = width.clamp(1, MAX_SIDE);
+                if self.keep_aspect {
+                    self.height = ((self.width as f64 / aspect).round() as u32).clamp(1, MAX_SIDE);
+                }
+            }
+            Msg::SetHeight(height) => {
+                self.height = height.clamp(1, MAX_SIDE);
+                if self.keep_aspect {
+                    self.width = ((self.height as f64 * aspect).round() as u32).clamp(1, MAX_SIDE);
+                }
+            }
+            Msg::SetKeepAspect(keep_aspect) => self.keep_aspect = keep_aspect,
+            Msg::PickFilter(filter) => self.filter = filter,
+            Msg::Scale => {
+                props.scale.emit((self.width, self.height, self.filter));
+                return false;
+            }
+            Msg::Cancel => {
+                props.cancel.emit(());
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let number = |value: u32, msg: fn(u32) -> Msg| {
+            html! {
+                <input
+                    type="number"
+                    min="1"
+                    value={value.to_string()}
+                    onchange={ctx.link().callback(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        msg(input.value().parse().unwrap_or(value))
+                    })}
+                />
+            }
+        };
+        html! {
+            <div class="dialog__overlay">
+                <div class="dialog tools__settings">
+                    <b>{"Scale image"}</b>
+                    <label>{"Width, px "}{number(self.width, Msg::SetWidth)}</label>
+                    <label>{"Height, px "}{number(self.height, Msg::SetHeight)}</label>
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked={self.keep_aspect}
+                            onchange={ctx.link().callback(|e: Event| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                Msg::SetKeepAspect(input.checked())
+                            })}
+                        />
+                        {"Keep aspect ratio"}
+                    </label>
+                    <label>
+                        {"Filter "}
+                        <select onchange={ctx.link().callback(|e: Event| {
+                            let select: HtmlSelectElement = e.target_unchecked_into();
+                            let index: usize = select.value().parse().unwrap_or_default();
+                            Msg::PickFilter(Filter::ALL[index.min(Filter::ALL.len() - 1)])
+                        })}>
+                            {
+                                for Filter::ALL.iter().enumerate().map(|(i, &filter)| html! {
+                                    <option
+                                        value={i.to_string()}
+                                        selected={filter == self.filter}
+                                    >{filter.name()}</option>
+                                })
+                            }
+                        </select>
+                    </label>
+                    <div class="presets__buttons">
+                        <button onclick={ctx.link().callback(|_| Msg::Scale)}>{"Scale"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::Cancel)}>{"Cancel"}</button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, InputEvent, Properties, TargetCast};
+
+use crate::{
+    airbrush::AirbrushSettings,
+    brush::{Brush, PaintMode},
+    crop::CropAspect,
+    dodge_burn::{DodgeBurn, ToneRange},
+    tool::Tool,
+};
+
+pub struct ToolsWidget;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub tool: Tool,
+    pub dodge_burn: DodgeBurn,
+    pub airbrush: AirbrushSettings,
+    pub brush: Brush,
+    #[prop_or_default]
+    pub shape_recognition: bool,
+    #[prop_or_default]
+    pub crop_aspect: CropAspect,
+    #[prop_or_default]
+    pub tool_pick: Callback<Tool>,
+    #[prop_or_default]
+    pub dodge_burn_change: Callback<DodgeBurn>,
+    #[prop_or_default]
+    pub airbrush_change: Callback<AirbrushSettings>,
+    #[prop_or_default]
+    pub brush_change: Callback<Brush>,
+    #[prop_or_default]
+    pub shape_recognition_change: Callback<bool>,
+    #[prop_or_default]
+    pub crop_aspect_change: Callback<CropAspect>,
+    #[prop_or_default]
+    pub crop_apply: Callback<()>,
+    #[prop_or_default]
+    pub crop_cancel: Callback<()>,
+}
+
+#[derive(Clone, Copy)]
+pub enum BrushField {
+    Size,
+    Spacing,
+    Angle,
+    Roundness,
+    Hardness,
+    Opacity,
+    Scatter,
+    SizeJitter,
+    AngleJitter,
+    RoundnessJitter,
+    Count,
+    HueJitter,
+    SaturationJitter,
+    BrightnessJitter,
+    ForegroundBackground,
+}
+
+impl BrushField {
+    /// Slider label and range, fractions are shown as percents.
+    fn describe(self) -> (&'static str, f64, f64) {
+        match self {
+            BrushField::Size => ("Size", 1.0, 300.0),
+            BrushField::Spacing => ("Spacing", 1.0, 200.0),
+            BrushField::Angle => ("Angle", -180.0, 180.0),
+            BrushField::Roundness => ("Roundness", 5.0, 100.0),
+            BrushField::Hardness => ("Hardness", 0.0, 100.0),
+            BrushField::Opacity => ("Opacity", 1.0, 100.0),
+            BrushField::Scatter => ("Scatter", 0.0, 300.0),
+            BrushField::SizeJitter => ("Size jitter", 0.0, 100.0),
+            BrushField::AngleJitter => ("Angle jitter", 0.0, 100.0),
+            BrushField::RoundnessJitter => ("Roundness jitter", 0.0, 100.0),
+            BrushField::Count => ("Count", 1.0, 16.0),
+            BrushField::HueJitter => ("Hue jitter", 0.0, 100.0),
+            BrushField::SaturationJitter => ("Saturation jitter", 0.0, 100.0),
+            BrushField::BrightnessJitter => ("Brightness jitter", 0.0, 100.0),
+            BrushField::ForegroundBackground => ("Foreground/background", 0.0, 100.0),
+        }
+    }
+
+    fn get(self, brush: &Brush) -> f64 {
+        let dynamics = &brush.dynamics;
+        match self {
+            BrushField::Size => brush.size,
+            BrushField::Spacing => brush.spacing * 100.0,
+            BrushField::Angle => brush.angle.to_degrees(),
+            BrushField::Roundness => brush.roundness * 100.0,
+            BrushField::Hardness => brush.hardness * 100.0,
+            BrushField::Opacity => brush.opacity * 100.0,
+            BrushField::Scatter => dynamics.scatter * 100.0,
+            BrushField::SizeJitter => dynamics.size_jitter * 100.0,
+            BrushField::AngleJitter => dynamics.angle_jitter * 100.0,
+            BrushField::RoundnessJitter => dynamics.roundness_jitter * 100.0,
+            BrushField::Count => dynamics.count as f64,
+            BrushField::HueJitter => dynamics.hue_jitter * 100.0,
+            BrushField::SaturationJitter => dynamics.saturation_jitter * 100.0,
+            BrushField::BrightnessJitter => dynamics.brightness_jitter * 100.0,
+            BrushField::ForegroundBackground => dynamics.foreground_background * 100.0,
+        }
+    }
+
+    fn set(self, brush: &mut Brush, value: f64) {
+        let dynamics = &mut brush.dynamics;
+        match self {
+            BrushField::Size => brush.size = value,
+            BrushField::Spacing => brush.spacing = value / 100.0,
+            BrushField::Angle => brush.angle = value.to_radians(),
+            BrushField::Roundness => brush.roundness = value / 100.0,
+            BrushField::Hardness => brush.hardness = value / 100.0,
+            BrushField::Opacity => brush.opacity = value / 100.0,
+            BrushField::Scatter => dynamics.scatter = value / 100.0,
+            BrushField::SizeJitter => dynamics.size_jitter = value / 100.0,
+            BrushField::AngleJitter => dynamics.angle_jitter = value / 100.0,
+            BrushField::RoundnessJitter => dynamics.roundness_jitter = value / 100.0,
+            BrushField::Count => dynamics.count = value as u32,
+            BrushField::HueJitter => dynamics.hue_jitter = value / 100.0,
+            BrushField::SaturationJitter => dynamics.saturation_jitter = value / 100.0,
+            BrushField::BrightnessJitter => dynamics.brightness_jitter = value / 100.0,
+            BrushField::ForegroundBackground => dynamics.foreground_background = value / 100.0,
+        }
+    }
+}
+
+pub enum Msg {
+    PickTool(Tool),
+    SetRange(ToneRange),
+    SetExposure(f64),
+    SetSaturate(bool),
+    SetFlow(f64),
+    SetRate(u32),
+    SetDensity(u32),
+    SetParticleSize(f64),
+    SetSeed(Option<u64>),
+    SetBrush(BrushField, f64),
+    SetPaintMode(PaintMode),
+    SetShapeRecognition(bool),
+    SetCropAspect(CropAspect),
+    ApplyCrop,
+    CancelCrop,
+}
+
+impl Component for ToolsWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let mut dodge_burn = props.dodge_burn;
+        let mut airbrush = props.airbrush;
+        let mut brush = props.brush;
+        match msg {
+            Msg::PickTool(tool) => props.tool_pick.emit(tool),
+            Msg::SetRange(range) => dodge_burn.range = range,
+            Msg::SetExposure(exposure) => dodge_burn.exposure = exposure,
+            Msg::SetSaturate(saturate) => dodge_burn.saturate = saturate,
+            Msg::SetFlow(flow) => airbrush.flow = flow,
+            Msg::SetRate(rate) => airbrush.rate = rate,
+            Msg::SetDensity(density) => airbrush.density = density,
+            Msg::SetParticleSize(size) => airbrush.particle_size = size,
+            Msg::SetSeed(seed) => airbrush.seed = seed,
+            Msg::SetBrush(field, value) => field.set(&mut brush, value),
+            Msg::SetPaintMode(mode) => brush.mode = mode,
+            Msg::SetShapeRecognition(enabled) => props.shape_recognition_change.emit(enabled),
+            Msg::SetCropAspect(aspect) => props.crop_aspect_change.emit(aspect),
+            Msg::ApplyCrop => props.crop_apply.emit(()),
+            Msg::CancelCrop => props.crop_cancel.emit(()),
+        }
+        if dodge_burn != props.dodge_burn {
+            props.dodge_burn_change.emit(dodge_burn);
+        }
+        if airbrush != props.airbrush {
+            props.airbrush_change.emit(airbrush);
+        }
+        if brush != props.brush {
+            props.brush_change.emit(brush);
+        }
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        html! {
+            <div class="tools__container">
+                <div class="tools__buttons">
+                    {
+                        for Tool::ALL.iter().map(|&tool| html! {
+                            <button
+                                class={if tool == props.tool { "tools__button tools__button_selected" } else { "tools__button" }}
+                                onclick={ctx.link().callback(move |_| Msg::PickTool(tool))}
+                            >{tool.name()}</button>
+                        })
+                    }
+                </div>
+                <div class="tools__settings">
+                    { self.view_brush_slider(ctx, BrushField::Size) }
+                </div>
+                {
+                    if props.tool == Tool::Brush {
+                        self.view_brush(ctx)
+                    } else if props.tool == Tool::Crop {
+                        self.view_crop(ctx)
+                    } else if props.tool.tone_mode(&props.dodge_burn).is_some() {
+                        self.view_dodge_burn(ctx)
+                    } else if props.tool.is_continuous() {
+                        self.view_airbrush(ctx)
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl ToolsWidget {
+    fn view_brush_slider(&self, ctx: &Context<Self>, field: BrushField) -> Html {
+        let (label, min, max) = field.describe();
+        html! {
+            <label>
+                {label}{" "}
+                <input
+                    type="range"
+                    min={min.to_string()}
+                    max={max.to_string()}
+                    value={(field.get(&ctx.props().brush).round() as i32).to_string()}
+                    oninput={ctx.link().callback(move |e: InputEvent| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        Msg::SetBrush(field, input.value_as_number())
+                    })}
+                />
+            </label>
+        }
+    }
+
+    fn view_brush(&self, ctx: &Context<Self>) -> Html {
+        let mode = ctx.props().brush.mode;
+        html! {
+            <div class="tools__settings">
+                <label>
+                    {"Mode "}
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        let index: usize = select.value().parse().unwrap_or_default();
+                        Msg::SetPaintMode(PaintMode::ALL.get(index).copied().unwrap_or_default())
+                    })}>
+                        {
+                            for PaintMode::ALL.iter().enumerate().map(|(i, &m)| html! {
+                                <option value={i.to_string()} selected={m == mode}>{m.name()}</option>
+                            })
+                        }
+                    </select>
+                </label>
+                <label title="Hold still at the end of a stroke to turn it into a shape">
+                    {"Recognize shapes "}
+                    <input
+                        type="checkbox"
+                        checked={ctx.props().shape_recognition}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetShapeRecognition(input.checked())
+                        })}
+                    />
+                </label>
+                {
+                    for [
+                        BrushField::Spacing,
+                        BrushField::Angle,
+                        BrushField::Roundness,
+                        BrushField::Hardness,
+                        BrushField::Opacity,
+                        BrushField::Scatter,
+                        BrushField::SizeJitter,
+                        BrushField::AngleJitter,
+                        BrushField::RoundnessJitter,
+                        BrushField::Count,
+                        BrushField::HueJitter,
+                        BrushField::SaturationJitter,
+                        BrushField::BrightnessJitter,
+                        BrushField::ForegroundBackground,
+                    ]
+                    .into_iter()
+                    .map(|field| self.view_brush_slider(ctx, field))
+                }
+            </div>
+        }
+    }
+
+    fn view_crop(&self, ctx: &Context<Self>) -> Html {
+        let aspect = ctx.props().crop_aspect;
+        html! {
+            <div class="tools__settings">
+                <label>
+                    {"Aspect ratio "}
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        let index: usize = select.value().parse().unwrap_or_default();
+                        Msg::SetCropAspect(CropAspect::ALL.get(index).copied().unwrap_or_default())
+                    })}>
+                        {
+                            for CropAspect::ALL.iter().enumerate().map(|(i, &a)| html! {
+                                <option value={i.to_string()} selected={a == aspect}>{a.name()}</option>
+                            })
+                        }
+                    </select>
+                </label>
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::ApplyCrop)}>{"Crop"}</button>
+                    <button onclick={ctx.link().callback(|_| Msg::CancelCrop)}>{"Cancel"}</button>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_dodge_burn(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let settings = props.dodge_burn;
+        html! {
+            <div class="tools__settings">
+                {
+                    if props.tool == Tool::Sponge {
+                        html! {
+                            <label>
+                                {"Mode "}
+                                <select onchange={ctx.link().callback(|e: Event| {
+                                    let select: HtmlSelectElement = e.target_unchecked_into();
+                                    Msg::SetSaturate(select.value() == "saturate")
+                                })}>
+                                    <option value="desaturate" selected={!settings.saturate}>{"Desaturate"}</option>
+                                    <option value="saturate" selected={settings.saturate}>{"Saturate"}</option>
+                                </select>
+                            </label>
+                        }
+                    } else {
+                        html! {
+                            <label>
+                                {"Range "}
+                                <select onchange={ctx.link().callback(|e: Event| {
+                                    let select: HtmlSelectElement = e.target_unchecked_into();
+                                    Msg::SetRange(match select.value().as_str() {
+                                        "shadows" => ToneRange::Shadows,
+                                        "highlights" => ToneRange::Highlights,
+                                        _ => ToneRange::Midtones,
+                                    })
+                                })}>
+                                    <option value="shadows" selected={settings.range == ToneRange::Shadows}>{"Shadows"}</option>
+                                    <option value="midtones" selected={settings.range == ToneRange::Midtones}>{"Midtones"}</option>
+                                    <option value="highlights" selected={settings.range == ToneRange::Highlights}>{"Highlights"}</option>
+                                </select>
+                            </label>
+                        }
+                    }
+                }
+                <label>
+                    {"Exposure "}
+                    <input
+                        type="range"
+                        min="0"
+                        max="100"
+                        value={((settings.exposure * 100.0) as i32).to_string()}
+                        oninput={ctx.link().callback(|e: InputEvent| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetExposure(input.value_as_number() / 100.0)
+                        })}
+                    />
+                </label>
+            </div>
+        }
+    }
+
+    fn view_airbrush(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let settings = props.airbrush;
+        html! {
+            <div class="tools__settings">
+                {
+                    if props.tool == Tool::Airbrush {
+                        html! {
+                            <label>
+                                {"Flow "}
+                                <input
+                                    type="range"
+                                    min="1"
+                                    max="100"
+                                    value={((settings.flow * 100.0) as i32).to_string()}
+                                    oninput={ctx.link().callback(|e: InputEvent| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        Msg::SetFlow(input.value_as_number() / 100.0)
+                                    })}
+                                />
+                            </label>
+                        }
+                    } else {
+                        html! {
+                            <>
+                                <label>
+                                    {"Density "}
+                                    <input
+                                        type="range"
+                                        min="1"
+                                        max="200"
+                                        value={settings.density.to_string()}
+                                        oninput={ctx.link().callback(|e: InputEvent| {
+                                            let input: HtmlInputElement = e.target_unchecked_into();
+                                            Msg::SetDensity(input.value_as_number() as u32)
+                                        })}
+                                    />
+                                </label>
+                                <label>
+                                    {"Particle size "}
+                                    <input
+                                        type="range"
+                                        min="1"
+                                        max="20"
+                                        value={(settings.particle_size as i32).to_string()}
+                                        oninput={ctx.link().callback(|e: InputEvent| {
+                                            let input: HtmlInputElement = e.target_unchecked_into();
+                                            Msg::SetParticleSize(input.value_as_number())
+                                        })}
+                                    />
+                                </label>
+                            </>
+                        }
+                    }
+                }
+                <label>
+                    {"Rate "}
+                    <input
+                        type="range"
+                        min="1"
+                        max="60"
+                        value={settings.rate.to_string()}
+                        oninput={ctx.link().callback(|e: InputEvent| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetRate(input.value_as_number() as u32)
+                        })}
+                    />
+                </label>
+                <label>
+                    {"Seed "}
+                    <input
+                        type="number"
+                        placeholder="random"
+                        value={settings.seed.map(|seed| seed.to_string()).unwrap_or_default()}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetSeed(input.value().parse().ok())
+                        })}
+                    />
+                </label>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+use crate::image_transform::Transformation;
+
+/// What rotations and flips apply to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Image,
+    SelectedLayer,
+}
+
+pub struct TransformWidget {
+    target: Target,
+    /// Degrees clockwise of the arbitrary rotation.
+    angle: f64,
+    expand: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    #[prop_or_default]
+    pub transform: Callback<(Target, Transformation)>,
+    /// Target, angle in radians and whether the canvas grows to fit.
+    #[prop_or_default]
+    pub rotate: Callback<(Target, f64, bool)>,
+}
+
+pub enum Msg {
+    PickTarget(Target),
+    Transform(Transformation),
+    SetAngle(f64),
+    SetExpand(bool),
+    Rotate,
+}
+
+impl Component for TransformWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            target: Target::Image,
+            angle: 15.0,
+            expand: true,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        match msg {
+            Msg::PickTarget(target) => self.target = target,
+            Msg::Transform(transformation) => {
+                props.transform.emit((self.target, transformation));
+                return false;
+            }
+            Msg::SetAngle(angle) => self.angle = angle.clamp(-360.0, 360.0),
+            Msg::SetExpand(expand) => self.expand = expand,
+            Msg::Rotate => {
+                props
+                    .rotate
+                    .emit((self.target, self.angle.to_radians(), self.expand));
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="tools__settings">
+                <label>
+                    {"Apply to "}
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let select: HtmlSelectElement = e.target_unchecked_into();
+                        Msg::PickTarget(if select.value() == "layer" {
+                            Target::SelectedLayer
+                        } else {
+                            Target::Image
+                        })
+                    })}>
+                        <option value="image" selected={self.target == Target::Image}>{"Image"}</option>
+                        <option value="layer" selected={self.target == Target::SelectedLayer}>{"Selected layer"}</option>
+                    </select>
+                </label>
+                {
+                    for Transformation::ALL.iter().map(|&transformation| html! {
+                        <button onclick={ctx.link().callback(move |_| Msg::Transform(transformation))}>
+                            {transformation.name()}
+                        </button>
+                    })
+                }
+                <label>
+                    {"Angle, ° "}
+                    <input
+                        type="number"
+                        min="-360"
+                        max="360"
+                        value={self.angle.to_string()}
+                        onchange={ctx.link().batch_callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            let angle = input.value_as_number();
+                            angle.is_finite().then_some(Msg::SetAngle(angle))
+                        })}
+                    />
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={self.expand}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::SetExpand(input.checked())
+                        })}
+                    />
+                    {"Expand canvas to fit"}
+                </label>
+                <button onclick={ctx.link().callback(|_| Msg::Rotate)}>{"Rotate"}</button>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use web_sys::HtmlInputElement;
+use yew::{html, Callback, Component, Context, Event, Html, InputEvent, Properties, TargetCast};
+
+use crate::{vector::Rectangle, viewport::Viewport};
+
+pub struct ViewWidget;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub viewport: Viewport,
+    pub document: Rectangle,
+    /// Whether a second view is open.
+    pub split: bool,
+    #[prop_or_default]
+    pub viewport_change: Callback<Viewport>,
+    #[prop_or_default]
+    pub split_change: Callback<bool>,
+}
+
+pub enum Msg {
+    Rotate(f64),
+    ResetRotation,
+    Flip(bool),
+    Fit,
+    ActualSize,
+    Split(bool),
+}
+
+impl Component for ViewWidget {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        let mut viewport = props.viewport;
+        match msg {
+            Msg::Rotate(degrees) => viewport.set_rotation(degrees.to_radians()),
+            Msg::ResetRotation => viewport.set_rotation(0.0),
+            Msg::Flip(flipped) => viewport.set_flipped(flipped),
+            Msg::Fit => viewport.fit(props.document),
+            Msg::ActualSize => viewport.actual_size(props.document),
+            Msg::Split(split) => {
+                props.split_change.emit(split);
+                return false;
+            }
+        }
+        props.viewport_change.emit(viewport);
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let viewport = ctx.props().viewport;
+        let degrees = viewport.rotation.to_degrees().round();
+        html! {
+            <div class="presets__container">
+                <label>
+                    {format!("Rotation {}°", degrees)}
+                    <input
+                        type="range"
+                        min="-180"
+                        max="180"
+                        value={degrees.to_string()}
+                        oninput={ctx.link().callback(|e: InputEvent| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::Rotate(input.value().parse().unwrap_or_default())
+                        })}
+                    />
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={viewport.flipped}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::Flip(input.checked())
+                        })}
+                    />
+                    {"Flip horizontally"}
+                </label>
+                <label>
+                    <input
+                        type="checkbox"
+                        checked={ctx.props().split}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            Msg::Split(input.checked())
+                        })}
+                    />
+                    {"Second view"}
+                </label>
+                <div>{format!("Zoom {:.0}%", viewport.zoom * 100.0)}</div>
+                <div class="presets__buttons">
+                    <button onclick={ctx.link().callback(|_| Msg::ResetRotation)}>{"Reset rotation"}</button>
+                    <button onclick={ctx.link().callback(|_| Msg::Fit)}>{"Fit"}</button>
+                    <button onclick={ctx.link().callback(|_| Msg::ActualSize)}>{"100%"}</button>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::vector::{Rectangle, Vector2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum CropAspect {
+    #[default]
+    Free,
+    /// Aspect ratio of the document.
+    Original,
+    Ratio(u32, u32),
+}
+
+impl CropAspect {
+    pub const ALL: [CropAspect; 7] = [
+        CropAspect::Free,
+        CropAspect::Original,
+        CropAspect::Ratio(1, 1),
+        CropAspect::Ratio(4, 3),
+        CropAspect::Ratio(3, 2),
+        CropAspect::Ratio(16, 9),
+        CropAspect::Ratio(9, 16),
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            CropAspect::Free => "Free".to_string(),
+            CropAspect::Original => "Original".to_string(),
+            CropAspect::Ratio(w, h) => format!("{}:{}", w, h),
+        }
+    }
+
+    /// Width divided by height, `None` when unconstrained.
+    pub fn ratio(self, document: Vector2) -> Option<f64> {
+        match self {
+            CropAspect::Free => None,
+            CropAspect::Original => Some(document.x / document.y),
+            CropAspect::Ratio(w, h) => Some(w as f64 / h as f64),
+        }
+    }
+}
+
+/// Rectangle dragged from `from` to `to`, shrunk towards `from` to keep the ratio.
+pub fn drag_rect(from: Vector2, to: Vector2, ratio: Option<f64>) -> Rectangle {
+    let mut size = Vector2::new((to.x - from.x).abs(), (to.y - from.y).abs());
+    if let Some(ratio) = ratio {
+        if size.x > size.y * ratio {
+            size.x = size.y * ratio;
+        } else {
+            size.y = size.x / ratio;
+        }
+    }
+    let x = if to.x < from.x {
+        from.x - size.x
+    } else {
+        from.x
+    };
+    let y = if to.y < from.y {
+        from.y - size.y
+    } else {
+        from.y
+    };
+    Rectangle::new(x, y, size.x, size.y)
+}
+
+/// Rectangle rounded to whole pixels and clamped to at least one pixel.
+pub fn round_rect(rect: Rectangle) -> (i32, i32, u32, u32) {
+    (
+        rect.coord.x.round() as i32,
+        rect.coord.y.round() as i32,
+        rect.size.x.round().max(1.0) as u32,
+        rect.size.y.round().max(1.0) as u32,
+    )
+}
+
+/// Lines dividing the rectangle into thirds.
+pub fn thirds(rect: Rectangle) -> Vec<Vec<Vector2>> {
+    let (x, y) = (rect.coord.x, rect.coord.y);
+    let (w, h) = (rect.size.x, rect.size.y);
+    (1..3)
+        .flat_map(|i| {
+            let k = i as f64 / 3.0;
+            [
+                vec![Vector2::new(x + w * k, y), Vector2::new(x + w * k, y + h)],
+                vec![Vector2::new(x, y + h * k), Vector2::new(x + w, y + h * k)],
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_drags_cover_both_points() {
+        let from = Vector2::new(50.0, 40.0);
+        assert_eq!(
+            drag_rect(from, Vector2::new(80.0, 100.0), None),
+            Rectangle::new(50.0, 40.0, 30.0, 60.0)
+        );
+        assert_eq!(
+            drag_rect(from, Vector2::new(20.0, 10.0), None),
+            Rectangle::new(20.0, 10.0, 30.0, 30.0)
+        );
+    }
+
+    #[test]
+    fn ratio_shrinks_towards_the_start() {
+        let from = Vector2::new(50.0, 40.0);
+        let ratio = CropAspect::Ratio(2, 1).ratio(Vector2::new(1.0, 1.0));
+        // Too wide, the width gives way
+        assert_eq!(
+            drag_rect(from, Vector2::new(150.0, 60.0), ratio),
+            Rectangle::new(50.0, 40.0, 40.0, 20.0)
+        );
+        // Too tall and dragged up and left, the rectangle still ends at the start
+        assert_eq!(
+            drag_rect(from, Vector2::new(10.0, -60.0), ratio),
+            Rectangle::new(10.0, 20.0, 40.0, 20.0)
+        );
+        assert_eq!(
+            drag_rect(from, Vector2::new(90.0, -60.0), ratio),
+            Rectangle::new(50.0, 20.0, 40.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn original_ratio_follows_the_document() {
+        let ratio = CropAspect::Original.ratio(Vector2::new(300.0, 100.0));
+        assert_eq!(ratio, Some(3.0));
+        assert_eq!(CropAspect::Free.ratio(Vector2::new(300.0, 100.0)), None);
+    }
+
+    #[test]
+    fn rounds_to_at_least_a_pixel() {
+        assert_eq!(
+            round_rect(Rectangle::new(-2.6, 3.4, 10.5, 0.2)),
+            (-3, 3, 11, 1)
+        );
+    }
+}
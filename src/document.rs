@@ -0,0 +1,200 @@
+use crate::{color::Color, vector::Vector2};
+
+/// Largest width or height of a new document.
+pub const MAX_SIDE: u32 = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+    White,
+    Transparent,
+    Custom(Color),
+}
+
+impl Background {
+    /// Color the bottom layer is filled with.
+    pub fn color(self) -> Option<Color> {
+        match self {
+            Background::White => Some(Color::WHITE),
+            Background::Transparent => None,
+            Background::Custom(color) => Some(color),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DocumentSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Pixels per inch, used for print sizes.
+    pub resolution: u32,
+    pub background: Background,
+    /// Layers grow as they're painted past the edges.
+    pub infinite: bool,
+}
+
+impl DocumentSettings {
+    /// Size in inches when printed at the resolution.
+    pub fn print_size(&self) -> (f64, f64) {
+        let resolution = self.resolution.max(1) as f64;
+        (
+            self.width as f64 / resolution,
+            self.height as f64 / resolution,
+        )
+    }
+}
+
+impl Default for DocumentSettings {
+    fn default() -> Self {
+        Self {
+            width: 1000,
+            height: 500,
+            resolution: 72,
+            background: Background::Transparent,
+            infinite: false,
+        }
+    }
+}
+
+/// Point of the canvas that stays in place when its size changes, one of a 3×3 grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    /// 0 for left, 1 for center, 2 for right.
+    pub column: u8,
+    /// 0 for top, 1 for center, 2 for bottom.
+    pub row: u8,
+}
+
+impl Anchor {
+    pub const CENTER: Anchor = Anchor { column: 1, row: 1 };
+
+    /// How far the old content moves when the canvas changes from `old` to `new` size.
+    pub fn offset(self, old: (u32, u32), new: (u32, u32)) -> Vector2 {
+        let shift =
+            |old: u32, new: u32, k: u8| ((new as f64 - old as f64) * k as f64 / 2.0).round();
+        Vector2::new(
+            shift(old.0, new.0, self.column),
+            shift(old.1, new.1, self.row),
+        )
+    }
+}
+
+pub struct Preset {
+    pub name: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub resolution: u32,
+}
+
+impl Preset {
+    /// Settings with the preset size and resolution, keeping the background.
+    pub fn apply(&self, settings: DocumentSettings) -> DocumentSettings {
+        DocumentSettings {
+            width: self.width,
+            height: self.height,
+            resolution: self.resolution,
+            ..settings
+        }
+    }
+}
+
+pub const PRESETS: [Preset; 7] = [
+    Preset {
+        name: "Screen 1280×720",
+        width: 1280,
+        height: 720,
+        resolution: 72,
+    },
+    Preset {
+        name: "Screen 1920×1080",
+        width: 1920,
+        height: 1080,
+        resolution: 72,
+    },
+    Preset {
+        name: "Square 2048",
+        width: 2048,
+        height: 2048,
+        resolution: 72,
+    },
+    Preset {
+        name: "A4, 300 dpi",
+        width: 2480,
+        height: 3508,
+        resolution: 300,
+    },
+    Preset {
+        name: "A5, 300 dpi",
+        width: 1748,
+        height: 2480,
+        resolution: 300,
+    },
+    Preset {
+        name: "Letter, 300 dpi",
+        width: 2550,
+        height: 3300,
+        resolution: 300,
+    },
+    Preset {
+        name: "Postcard, 300 dpi",
+        width: 1800,
+        height: 1200,
+        resolution: 300,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(old: (u32, u32), new: (u32, u32)) -> Vec<(f64, f64)> {
+        (0..3)
+            .flat_map(|row| (0..3).map(move |column| Anchor { column, row }))
+            .map(|anchor| {
+                let offset = anchor.offset(old, new);
+                (offset.x, offset.y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn growing_moves_content_away_from_the_anchor() {
+        assert_eq!(
+            offsets((100, 50), (140, 90)),
+            [
+                (0.0, 0.0),
+                (20.0, 0.0),
+                (40.0, 0.0),
+                (0.0, 20.0),
+                (20.0, 20.0),
+                (40.0, 20.0),
+                (0.0, 40.0),
+                (20.0, 40.0),
+                (40.0, 40.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn shrinking_cuts_away_from_the_anchor() {
+        assert_eq!(
+            offsets((100, 50), (60, 30)),
+            [
+                (0.0, 0.0),
+                (-20.0, 0.0),
+                (-40.0, 0.0),
+                (0.0, -10.0),
+                (-20.0, -10.0),
+                (-40.0, -10.0),
+                (0.0, -20.0),
+                (-20.0, -20.0),
+                (-40.0, -20.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn center_offsets_are_whole_pixels() {
+        let offset = Anchor::CENTER.offset((100, 100), (103, 96));
+        assert_eq!((offset.x, offset.y), (2.0, -2.0));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::color::Color;
+
+/// Distance between the dabs of a stroke, relative to the brush radius.
+pub const DAB_SPACING: f64 = 0.25;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMode {
+    Dodge,
+    Burn,
+    Saturate,
+    Desaturate,
+}
+
+impl ToneMode {
+    pub fn to_shader_index(self) -> i32 {
+        match self {
+            ToneMode::Dodge => 0,
+            ToneMode::Burn => 1,
+            ToneMode::Saturate => 2,
+            ToneMode::Desaturate => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneRange {
+    Shadows,
+    Midtones,
+    Highlights,
+}
+
+impl ToneRange {
+    pub fn to_shader_index(self) -> i32 {
+        match self {
+            ToneRange::Shadows => 0,
+            ToneRange::Midtones => 1,
+            ToneRange::Highlights => 2,
+        }
+    }
+}
+
+/// Settings of the dodge, burn and sponge tools.
+///
+/// `exposure` is in `0.0..=1.0`. The same math is implemented by
+/// `shaders/src/dodge_burn.frag`, keep them in sync.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DodgeBurn {
+    pub range: ToneRange,
+    pub exposure: f64,
+    pub saturate: bool,
+}
+
+impl DodgeBurn {
+    pub fn new(range: ToneRange, exposure: f64, saturate: bool) -> Self {
+        Self {
+            range,
+            exposure,
+            saturate,
+        }
+    }
+
+    pub fn sponge_mode(&self) -> ToneMode {
+        if self.saturate {
+            ToneMode::Saturate
+        } else {
+            ToneMode::Desaturate
+        }
+    }
+
+    /// Settings for one dab of a stroke, so that a stroke applies `exposure` once per
+    /// radius it travels instead of once per dab.
+    pub fn per_dab(&self) -> Self {
+        Self {
+            exposure: self.exposure * DAB_SPACING,
+            ..*self
+        }
+    }
+
+    /// Applies the tool to a color, `strength` is the brush coverage in `0.0..=1.0`.
+    pub fn apply(&self, mode: ToneMode, color: Color, strength: f64) -> Color {
+        let (r, g, b, a) = color.to_float();
+
+        let (nr, ng, nb) = match mode {
+            ToneMode::Dodge | ToneMode::Burn => (
+                self.tone_channel(mode, r),
+                self.tone_channel(mode, g),
+                self.tone_channel(mode, b),
+            ),
+            ToneMode::Saturate | ToneMode::Desaturate => self.sponge(mode, r, g, b),
+        };
+
+        let strength = strength.clamp(0.0, 1.0);
+
+        Color::from_float(
+            mix(r, nr, strength),
+            mix(g, ng, strength),
+            mix(b, nb, strength),
+            a,
+        )
+    }
+
+    fn tone_channel(&self, mode: ToneMode, v: f64) -> f64 {
+        let exposure = self.exposure.clamp(0.0, 1.0);
+
+        let v = match (mode, self.range) {
+            (ToneMode::Dodge, ToneRange::Highlights) => v * (1.0 + exposure / 3.0),
+            (ToneMode::Dodge, ToneRange::Midtones) => v.powf(1.0 / (1.0 + exposure)),
+            (ToneMode::Dodge, ToneRange::Shadows) => {
+                let factor = exposure / 3.0;
+                factor + v - factor * v
+            }
+            (ToneMode::Burn, ToneRange::Highlights) => v * (1.0 - exposure / 3.0),
+            (ToneMode::Burn, ToneRange::Midtones) => v.powf(1.0 + exposure),
+            (ToneMode::Burn, ToneRange::Shadows) => {
+                let factor = exposure / 3.0;
+                (v - factor) / (1.0 - factor)
+            }
+            _ => v,
+        };
+
+        v.clamp(0.0, 1.0)
+    }
+
+    fn sponge(&self, mode: ToneMode, r: f64, g: f64, b: f64) -> (f64, f64, f64) {
+        let exposure = self.exposure.clamp(0.0, 1.0);
+        let luma = luminance(r, g, b);
+
+        let factor = match mode {
+            ToneMode::Saturate => 1.0 + exposure,
+            _ => 1.0 - exposure,
+        };
+
+        let f = |v: f64| (luma + (v - luma) * factor).clamp(0.0, 1.0);
+
+        (f(r), f(g), f(b))
+    }
+}
+
+impl Default for DodgeBurn {
+    fn default() -> Self {
+        Self::new(ToneRange::Midtones, 0.5, false)
+    }
+}
+
+pub fn luminance(r: f64, g: f64, b: f64) -> f64 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn mix(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(v: u8) -> Color {
+        Color::new(v, v, v, 255)
+    }
+
+    /// Red channel after applying the tool at full strength.
+    fn tone(mode: ToneMode, range: ToneRange, exposure: f64, v: u8) -> i32 {
+        DodgeBurn::new(range, exposure, false)
+            .apply(mode, gray(v), 1.0)
+            .get_r() as i32
+    }
+
+    fn assert_near(actual: i32, expected: i32) {
+        assert!((actual - expected).abs() <= 1, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn zero_exposure_keeps_colors() {
+        let color = Color::new(200, 100, 30, 255);
+        for mode in [
+            ToneMode::Dodge,
+            ToneMode::Burn,
+            ToneMode::Saturate,
+            ToneMode::Desaturate,
+        ] {
+            for range in [
+                ToneRange::Shadows,
+                ToneRange::Midtones,
+                ToneRange::Highlights,
+            ] {
+                let result = DodgeBurn::new(range, 0.0, false).apply(mode, color, 1.0);
+                assert_near(result.get_r() as i32, 200);
+                assert_near(result.get_g() as i32, 100);
+                assert_near(result.get_b() as i32, 30);
+            }
+        }
+    }
+
+    #[test]
+    fn shadows_change_dark_tones_most() {
+        let dark = tone(ToneMode::Dodge, ToneRange::Shadows, 1.0, 0);
+        let bright = tone(ToneMode::Dodge, ToneRange::Shadows, 1.0, 200) - 200;
+        assert_near(dark, 85);
+        assert!(dark > bright);
+        assert_near(tone(ToneMode::Burn, ToneRange::Shadows, 1.0, 85), 0);
+        assert_near(tone(ToneMode::Burn, ToneRange::Shadows, 1.0, 255), 255);
+    }
+
+    #[test]
+    fn midtones_keep_black_and_white() {
+        for mode in [ToneMode::Dodge, ToneMode::Burn] {
+            assert_near(tone(mode, ToneRange::Midtones, 1.0, 0), 0);
+            assert_near(tone(mode, ToneRange::Midtones, 1.0, 255), 255);
+        }
+        assert_near(tone(ToneMode::Dodge, ToneRange::Midtones, 1.0, 64), 128);
+        assert_near(tone(ToneMode::Burn, ToneRange::Midtones, 1.0, 128), 64);
+    }
+
+    #[test]
+    fn highlights_change_bright_tones_most() {
+        let bright = 200 - tone(ToneMode::Burn, ToneRange::Highlights, 1.0, 200);
+        let dark = 20 - tone(ToneMode::Burn, ToneRange::Highlights, 1.0, 20);
+        assert!(bright > dark);
+        assert_near(tone(ToneMode::Burn, ToneRange::Highlights, 1.0, 255), 170);
+        assert_near(tone(ToneMode::Dodge, ToneRange::Highlights, 1.0, 255), 255);
+        assert_near(tone(ToneMode::Dodge, ToneRange::Highlights, 1.0, 150), 200);
+    }
+
+    #[test]
+    fn exposure_is_clamped() {
+        for range in [
+            ToneRange::Shadows,
+            ToneRange::Midtones,
+            ToneRange::Highlights,
+        ] {
+            for mode in [ToneMode::Dodge, ToneMode::Burn] {
+                assert_eq!(tone(mode, range, 5.0, 100), tone(mode, range, 1.0, 100));
+                assert_eq!(tone(mode, range, -1.0, 100), 100);
+            }
+        }
+    }
+
+    #[test]
+    fn strength_mixes_and_alpha_is_kept() {
+        let settings = DodgeBurn::new(ToneRange::Midtones, 1.0, false);
+        let color = Color::new(128, 128, 128, 77);
+        assert_eq!(settings.apply(ToneMode::Burn, color, 0.0), color);
+        let half = settings.apply(ToneMode::Burn, color, 0.5);
+        assert_near(half.get_r() as i32, 96);
+        assert_eq!(half.get_a(), 77);
+    }
+
+    #[test]
+    fn sponge_changes_saturation() {
+        let color = Color::new(180, 90, 60, 255);
+        let (_, s, _) = color.to_hsv();
+
+        let saturated = DodgeBurn::new(ToneRange::Midtones, 0.5, true);
+        let (_, more, _) = saturated
+            .apply(saturated.sponge_mode(), color, 1.0)
+            .to_hsv();
+        assert!(more > s);
+
+        let desaturated = DodgeBurn::new(ToneRange::Midtones, 1.0, false);
+        let gray = desaturated.apply(desaturated.sponge_mode(), color, 1.0);
+        assert_eq!(gray.get_r(), gray.get_g());
+        assert_eq!(gray.get_g(), gray.get_b());
+        let (r, g, b, _) = color.to_float();
+        assert_near(gray.get_r() as i32, (luminance(r, g, b) * 255.0) as i32);
+    }
+
+    #[test]
+    fn dabs_share_the_exposure() {
+        let settings = DodgeBurn::new(ToneRange::Shadows, 0.8, false).per_dab();
+        assert!((settings.exposure - 0.8 * DAB_SPACING).abs() < 1e-12);
+        assert_eq!(settings.range, ToneRange::Shadows);
+    }
+
+    /// Expected outputs also listed in `shaders/src/dodge_burn.frag`, exposure 0.6.
+    #[test]
+    fn reference_values() {
+        let settings = DodgeBurn::new(ToneRange::Shadows, 0.6, false);
+        for (mode, range, expected) in [
+            (ToneMode::Dodge, ToneRange::Shadows, 0.6),
+            (ToneMode::Dodge, ToneRange::Midtones, 0.64842),
+            (ToneMode::Dodge, ToneRange::Highlights, 0.6),
+            (ToneMode::Burn, ToneRange::Shadows, 0.375),
+            (ToneMode::Burn, ToneRange::Midtones, 0.32988),
+            (ToneMode::Burn, ToneRange::Highlights, 0.4),
+        ] {
+            let actual = DodgeBurn { range, ..settings }.tone_channel(mode, 0.5);
+            assert!((actual - expected).abs() < 1e-5, "{:?} {:?}", mode, range);
+        }
+
+        for (mode, expected) in [
+            (ToneMode::Saturate, (0.99764, 0.35764, 0.03764)),
+            (ToneMode::Desaturate, (0.60236, 0.44236, 0.36236)),
+        ] {
+            let (r, g, b) = settings.sponge(mode, 0.8, 0.4, 0.2);
+            for (actual, expected) in [(r, expected.0), (g, expected.1), (b, expected.2)] {
+                assert!((actual - expected).abs() < 1e-5, "{:?}", mode);
+            }
+        }
+    }
+}
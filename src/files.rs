@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use gloo::{events::EventListener, file::Blob, timers::callback::Timeout, utils::document};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlAnchorElement, HtmlCanvasElement, HtmlImageElement, Url};
+
+use crate::{base64, raster::RgbaImage, virtual_context::image_from_element};
+
+/// How long a download URL stays valid after the download is offered.
+const REVOKE_DELAY_MILLIS: u32 = 60_000;
+
+/// Offers the contents to the user as a file download.
+pub fn download(name: &str, mime_type: &str, contents: &[u8]) {
+    let blob = Blob::new_with_options(contents, Some(mime_type));
+    let url = Url::create_object_url_with_blob(&blob.into()).unwrap();
+
+    let anchor: HtmlAnchorElement = document().create_element("a").unwrap().unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    // The browser may start the download after the click returns
+    Timeout::new(REVOKE_DELAY_MILLIS, move || {
+        Url::revoke_object_url(&url).unwrap();
+    })
+    .forget();
+}
+
+/// Contents of a PNG file with the pixels of the canvas.
+pub fn canvas_to_png(canvas: &HtmlCanvasElement) -> Vec<u8> {
+    let url = canvas.to_data_url().unwrap();
+    let (_, data) = url.split_once(',').unwrap_or_default();
+    base64::decode(data).unwrap_or_default()
+}
+
+/// Decodes an image file with the browser, loading stops when the loader is dropped.
+pub struct ImageLoader {
+    _load: EventListener,
+    _error: EventListener,
+}
+
+impl ImageLoader {
+    /// `on_load` gets the pixels or `None` when the file isn't an image.
+    pub fn new(file: &web_sys::File, on_load: impl Fn(Option<RgbaImage>) + 'static) -> Self {
+        let url = Url::create_object_url_with_blob(file).unwrap();
+        let image = HtmlImageElement::new().unwrap();
+        let on_load = Rc::new(on_load);
+
+        let load = {
+            let (url, element, on_load) = (url.clone(), image.clone(), on_load.clone());
+            EventListener::once(&image, "load", move |_| {
+                Url::revoke_object_url(&url).unwrap();
+                on_load(Some(image_from_element(&element)));
+            })
+        };
+        let error = {
+            let url = url.clone();
+            EventListener::once(&image, "error", move |_| {
+                Url::revoke_object_url(&url).unwrap();
+                on_load(None);
+            })
+        };
+
+        image.set_src(&url);
+        Self {
+            _load: load,
+            _error: error,
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use crate::raster::{GrayImage, RgbaImage};
+
+/// Longest side of a brush or pattern that is read, larger files are rejected
+/// instead of allocated.
+pub const MAX_SIDE: u32 = 4096;
+
+#[derive(Debug, PartialEq)]
+pub enum FormatError {
+    UnexpectedEnd,
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnsupportedDepth(u32),
+    InvalidSize(u32, u32),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::UnexpectedEnd => write!(f, "file is truncated"),
+            FormatError::BadMagic => write!(f, "not a GIMP brush or pattern"),
+            FormatError::UnsupportedVersion(v) => write!(f, "unsupported version {}", v),
+            FormatError::UnsupportedDepth(d) => write!(f, "unsupported {} bytes per pixel", d),
+            FormatError::InvalidSize(w, h) => write!(f, "unsupported size {}x{}", w, h),
+        }
+    }
+}
+
+/// Reads a GIMP `.gbr` brush, color brushes are turned into a mask by their alpha.
+///
+/// Both `.gbr` and `.pat` start with a big-endian header: header size, version, width,
+/// height, bytes per pixel, a magic string and the UTF-8 name, followed by the pixels.
+pub fn parse_gbr(bytes: &[u8]) -> Result<(String, GrayImage), FormatError> {
+    let header_size = read_u32(bytes, 0)?;
+    let version = read_u32(bytes, 4)?;
+    let width = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let depth = read_u32(bytes, 16)?;
+
+    let name_start = match version {
+        1 => 20,
+        2 | 3 => {
+            if bytes.get(20..24) != Some(b"GIMP") {
+                return Err(FormatError::BadMagic);
+            }
+            28
+        }
+        v => return Err(FormatError::UnsupportedVersion(v)),
+    };
+
+    let name = read_name(bytes, name_start, header_size as usize)?;
+    let pixels = read_pixels(bytes, header_size as usize, width, height, depth)?;
+
+    let data = match depth {
+        1 => pixels.to_vec(),
+        4 => pixels.chunks(4).map(|p| p[3]).collect(),
+        d => return Err(FormatError::UnsupportedDepth(d)),
+    };
+
+    let image = GrayImage::from_data(width, height, data).ok_or(FormatError::UnexpectedEnd)?;
+    Ok((name, image))
+}
+
+/// Reads a GIMP `.pat` pattern.
+pub fn parse_pat(bytes: &[u8]) -> Result<(String, RgbaImage), FormatError> {
+    let header_size = read_u32(bytes, 0)?;
+    let version = read_u32(bytes, 4)?;
+    let width = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let depth = read_u32(bytes, 16)?;
+
+    if version != 1 {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    if bytes.get(20..24) != Some(b"GPAT") {
+        return Err(FormatError::BadMagic);
+    }
+
+    let name = read_name(bytes, 24, header_size as usize)?;
+    let pixels = read_pixels(bytes, header_size as usize, width, height, depth)?;
+
+    let data = match depth {
+        1 => pixels.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        2 => pixels
+            .chunks(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        3 => pixels
+            .chunks(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        4 => pixels.to_vec(),
+        d => return Err(FormatError::UnsupportedDepth(d)),
+    };
+
+    let image = RgbaImage::from_data(width, height, data).ok_or(FormatError::UnexpectedEnd)?;
+    Ok((name, image))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, FormatError> {
+    let slice = bytes
+        .get(offset..offset.checked_add(4).ok_or(FormatError::UnexpectedEnd)?)
+        .ok_or(FormatError::UnexpectedEnd)?;
+    Ok(u32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn read_name(bytes: &[u8], start: usize, end: usize) -> Result<String, FormatError> {
+    let raw = bytes.get(start..end).ok_or(FormatError::UnexpectedEnd)?;
+    let raw = raw.split(|&b| b == 0).next().unwrap_or_default();
+    Ok(String::from_utf8_lossy(raw).into_owned())
+}
+
+fn read_pixels(
+    bytes: &[u8],
+    start: usize,
+    width: u32,
+    height: u32,
+    depth: u32,
+) -> Result<&[u8], FormatError> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(FormatError::InvalidSize(width, height));
+    }
+    let end = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|len| len.checked_mul(depth as usize))
+        .and_then(|len| len.checked_add(start))
+        .ok_or(FormatError::UnexpectedEnd)?;
+    bytes.get(start..end).ok_or(FormatError::UnexpectedEnd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// File with the given header fields, name and pixels.
+    fn file(version: u32, size: (u32, u32), depth: u32, magic: &[u8], pixels: &[u8]) -> Vec<u8> {
+        let name = b"test\0";
+        let header_size = 20 + magic.len() + name.len();
+        let mut bytes = vec![];
+        for field in [header_size as u32, version, size.0, size.1, depth] {
+            bytes.extend(field.to_be_bytes());
+        }
+        bytes.extend(magic);
+        bytes.extend(name);
+        bytes.extend(pixels);
+        bytes
+    }
+
+    fn gbr(size: (u32, u32), depth: u32, pixels: &[u8]) -> Vec<u8> {
+        // Spacing follows the magic in version 2
+        file(2, size, depth, b"GIMP\0\0\0\x19", pixels)
+    }
+
+    fn pat(size: (u32, u32), depth: u32, pixels: &[u8]) -> Vec<u8> {
+        file(1, size, depth, b"GPAT", pixels)
+    }
+
+    #[test]
+    fn reads_gray_brush() {
+        let (name, image) = parse_gbr(&gbr((2, 1), 1, &[10, 200])).unwrap();
+        assert_eq!(name, "test");
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.data, [10, 200]);
+    }
+
+    #[test]
+    fn reads_color_brush_alpha() {
+        let (_, image) = parse_gbr(&gbr((1, 1), 4, &[1, 2, 3, 99])).unwrap();
+        assert_eq!(image.data, [99]);
+    }
+
+    #[test]
+    fn reads_version_1_brush() {
+        let (name, image) = parse_gbr(&file(1, (1, 1), 1, b"", &[7])).unwrap();
+        assert_eq!(name, "test");
+        assert_eq!(image.data, [7]);
+    }
+
+    #[test]
+    fn reads_patterns_of_every_depth() {
+        let cases: [(u32, &[u8], [u8; 4]); 4] = [
+            (1, &[9], [9, 9, 9, 255]),
+            (2, &[9, 50], [9, 9, 9, 50]),
+            (3, &[1, 2, 3], [1, 2, 3, 255]),
+            (4, &[1, 2, 3, 4], [1, 2, 3, 4]),
+        ];
+        for (depth, pixels, rgba) in cases {
+            let (name, image) = parse_pat(&pat((1, 1), depth, pixels)).unwrap();
+            assert_eq!(name, "test");
+            assert_eq!(image.data, rgba);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(parse_gbr(&[0; 10]), Err(FormatError::UnexpectedEnd));
+        assert_eq!(
+            parse_gbr(&file(2, (1, 1), 1, b"NOPE\0\0\0\0", &[0])),
+            Err(FormatError::BadMagic)
+        );
+        assert_eq!(
+            parse_pat(&file(1, (1, 1), 1, b"NOPE", &[0])),
+            Err(FormatError::BadMagic)
+        );
+        assert_eq!(
+            parse_gbr(&file(9, (1, 1), 1, b"", &[0])),
+            Err(FormatError::UnsupportedVersion(9))
+        );
+        assert_eq!(
+            parse_pat(&pat((1, 1), 5, &[0; 5])),
+            Err(FormatError::UnsupportedDepth(5))
+        );
+        assert_eq!(
+            parse_pat(&pat((2, 2), 3, &[0; 11])),
+            Err(FormatError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_huge_sizes() {
+        assert_eq!(
+            parse_gbr(&gbr((0, 0), 1, &[])),
+            Err(FormatError::InvalidSize(0, 0))
+        );
+        assert_eq!(
+            parse_pat(&pat((u32::MAX, u32::MAX), u32::MAX, &[0])),
+            Err(FormatError::InvalidSize(u32::MAX, u32::MAX))
+        );
+        assert_eq!(
+            parse_pat(&pat((MAX_SIDE, MAX_SIDE), 4, &[0])),
+            Err(FormatError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn rejects_header_past_the_end() {
+        let mut bytes = pat((1, 1), 1, &[0]);
+        bytes[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(parse_pat(&bytes), Err(FormatError::UnexpectedEnd));
+    }
+}
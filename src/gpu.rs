@@ -0,0 +1,247 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use gloo::utils::document;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext, WebGl2RenderingContext as Gl, WebGlFramebuffer,
+    WebGlTexture,
+};
+
+use crate::{
+    shaders::{
+        checkerboard::CheckerboardShader, composite::CompositeShader, copy_image::CopyImageShader,
+        dodge_burn::DodgeBurnShader, hsv_circle::HsvCircleShader, load_texture_from_canvas,
+    },
+    vector::Rectangle,
+};
+
+thread_local! {
+    static SHARED: Rc<RefCell<Gpu>> = Rc::new(RefCell::new(Gpu::new()));
+}
+
+/// The one WebGL2 context of the page with its compiled shaders, browsers only allow
+/// a handful of contexts at once.
+///
+/// Surfaces keep a copy of their pixels in a texture here and render into a
+/// `RenderTarget` of their own size. The canvas of the context only grows to the largest
+/// surface and gets the result of a pass for the 2D canvas to read.
+pub struct Gpu {
+    canvas: HtmlCanvasElement,
+    gl: WebGl2RenderingContext,
+
+    pub hsv_circle: HsvCircleShader,
+    pub copy_image: CopyImageShader,
+    pub checkerboard: CheckerboardShader,
+    pub dodge_burn: DodgeBurnShader,
+    pub composite: CompositeShader,
+}
+
+/// Texture a surface renders into, attached to its own framebuffer.
+pub struct RenderTarget {
+    framebuffer: WebGlFramebuffer,
+    texture: WebGlTexture,
+    size: Cell<(u32, u32)>,
+}
+
+impl Gpu {
+    fn new() -> Self {
+        let canvas: HtmlCanvasElement = document()
+            .create_element("canvas")
+            .unwrap()
+            .unchecked_into();
+        canvas.set_width(1);
+        canvas.set_height(1);
+        let gl = canvas
+            .get_context("webgl2")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<WebGl2RenderingContext>()
+            .unwrap();
+        Self {
+            hsv_circle: HsvCircleShader::new(&gl, 1, 1),
+            copy_image: CopyImageShader::new(&gl, 1, 1),
+            checkerboard: CheckerboardShader::new(&gl, 1, 1),
+            dodge_burn: DodgeBurnShader::new(&gl, 1, 1),
+            composite: CompositeShader::new(&gl, 1, 1),
+            canvas,
+            gl,
+        }
+    }
+
+    pub fn shared() -> Rc<RefCell<Gpu>> {
+        SHARED.with(Rc::clone)
+    }
+
+    pub fn gl(&self) -> &WebGl2RenderingContext {
+        &self.gl
+    }
+
+    pub fn canvas(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    /// New texture sampled by the nearest pixel unless set otherwise.
+    pub fn create_texture(&self) -> WebGlTexture {
+        let texture = self.gl.create_texture().unwrap();
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        for parameter in [Gl::TEXTURE_MIN_FILTER, Gl::TEXTURE_MAG_FILTER] {
+            self.gl
+                .tex_parameteri(Gl::TEXTURE_2D, parameter, Gl::NEAREST as i32);
+        }
+        texture
+    }
+
+    pub fn delete_texture(&self, texture: &WebGlTexture) {
+        self.gl.delete_texture(Some(texture));
+    }
+
+    /// Render target without storage, `begin` sizes it.
+    pub fn create_target(&self) -> RenderTarget {
+        RenderTarget {
+            framebuffer: self.gl.create_framebuffer().unwrap(),
+            texture: self.create_texture(),
+            size: Cell::new((0, 0)),
+        }
+    }
+
+    pub fn delete_target(&self, target: &RenderTarget) {
+        self.gl.delete_framebuffer(Some(&target.framebuffer));
+        self.gl.delete_texture(Some(&target.texture));
+    }
+
+    pub fn upload(&self, texture: &WebGlTexture, canvas: &HtmlCanvasElement) {
+        load_texture_from_canvas(&self.gl, texture, canvas);
+    }
+
+    /// Uploads with the colors premultiplied by alpha, so that filtering doesn't blend
+    /// in the color of transparent pixels, and mipmaps up to `max_level`.
+    pub fn upload_premultiplied(
+        &self,
+        texture: &WebGlTexture,
+        canvas: &HtmlCanvasElement,
+        max_level: u32,
+    ) {
+        let gl = &self.gl;
+        gl.pixel_storei(Gl::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 1);
+        gl.bind_texture(Gl::TEXTURE_2D, Some(texture));
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAX_LEVEL, max_level as i32);
+        load_texture_from_canvas(gl, texture, canvas);
+        gl.pixel_storei(Gl::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 0);
+    }
+
+    /// Replaces the `region` of a texture uploaded from a canvas of the same size.
+    pub fn upload_region(
+        &self,
+        texture: &WebGlTexture,
+        canvas: &HtmlCanvasElement,
+        region: Rectangle,
+    ) {
+        let (x, y) = (region.coord.x as i32, region.coord.y as i32);
+        let gl = &self.gl;
+        gl.bind_texture(Gl::TEXTURE_2D, Some(texture));
+        gl.pixel_storei(Gl::UNPACK_SKIP_PIXELS, x);
+        gl.pixel_storei(Gl::UNPACK_SKIP_ROWS, y);
+        gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_html_canvas_element(
+            Gl::TEXTURE_2D,
+            0,
+            x,
+            y,
+            region.size.x as i32,
+            region.size.y as i32,
+            Gl::RGBA,
+            Gl::UNSIGNED_BYTE,
+            canvas,
+        )
+        .unwrap();
+        gl.pixel_storei(Gl::UNPACK_SKIP_PIXELS, 0);
+        gl.pixel_storei(Gl::UNPACK_SKIP_ROWS, 0);
+    }
+
+    /// Gets `target` ready for drawing a surface of the given size, starting from the
+    /// pixels of `source` if given and from transparent pixels otherwise.
+    pub fn begin(
+        &mut self,
+        target: &RenderTarget,
+        source: Option<&WebGlTexture>,
+        width: u32,
+        height: u32,
+    ) {
+        let gl = &self.gl;
+        let (w, h) = (width as i32, height as i32);
+        if target.size.get() != (width, height) {
+            gl.bind_texture(Gl::TEXTURE_2D, Some(&target.texture));
+            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGBA as i32,
+                w,
+                h,
+                0,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                None,
+            )
+            .unwrap();
+            gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&target.framebuffer));
+            gl.framebuffer_texture_2d(
+                Gl::FRAMEBUFFER,
+                Gl::COLOR_ATTACHMENT0,
+                Gl::TEXTURE_2D,
+                Some(&target.texture),
+                0,
+            );
+            target.size.set((width, height));
+        }
+        // The canvas only holds finished passes, it never shrinks
+        if self.canvas.width() < width || self.canvas.height() < height {
+            self.canvas.set_width(self.canvas.width().max(width));
+            self.canvas.set_height(self.canvas.height().max(height));
+        }
+
+        gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&target.framebuffer));
+        self.hsv_circle.set_size(w, h);
+        self.copy_image.set_size(w, h);
+        self.checkerboard.set_size(w, h);
+        self.dodge_burn.set_size(w, h);
+        self.composite.set_size(w, h);
+
+        match source {
+            Some(texture) => {
+                gl.bind_texture(Gl::TEXTURE_2D, Some(texture));
+                self.copy_image.draw(gl, 0, 0, w, h, texture);
+            }
+            None => {
+                gl.viewport(0, 0, w, h);
+                gl.clear_color(0.0, 0.0, 0.0, 0.0);
+                gl.clear(Gl::COLOR_BUFFER_BIT);
+            }
+        }
+    }
+
+    /// Copies what was drawn on `target` to the top left corner of the canvas.
+    pub fn present(&self, target: &RenderTarget) {
+        let gl = &self.gl;
+        let (w, h) = target.size.get();
+        let (w, h) = (w as i32, h as i32);
+        let canvas_height = self.canvas.height() as i32;
+        gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, Some(&target.framebuffer));
+        gl.bind_framebuffer(Gl::DRAW_FRAMEBUFFER, None);
+        // GL rows go up, the top of the canvas is its last row
+        gl.blit_framebuffer(
+            0,
+            0,
+            w,
+            h,
+            0,
+            canvas_height - h,
+            w,
+            canvas_height,
+            Gl::COLOR_BUFFER_BIT,
+            Gl::NEAREST,
+        );
+        gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+    }
+}
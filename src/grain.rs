@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use crate::raster::GrayImage;
+
+/// Side of the generated paper textures, they tile seamlessly.
+pub const PAPER_SIZE: u32 = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Paper {
+    ColdPress,
+    Rough,
+    Canvas,
+    Laid,
+}
+
+impl Paper {
+    pub const ALL: [Paper; 4] = [Paper::ColdPress, Paper::Rough, Paper::Canvas, Paper::Laid];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Paper::ColdPress => "Cold press",
+            Paper::Rough => "Rough",
+            Paper::Canvas => "Canvas",
+            Paper::Laid => "Laid",
+        }
+    }
+
+    pub fn generate(self) -> GrayImage {
+        let size = PAPER_SIZE;
+        let mut data = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f64, y as f64);
+                let value = match self {
+                    Paper::ColdPress => fbm(u, v, 32, 4, 1),
+                    Paper::Rough => {
+                        let base = fbm(u, v, 64, 3, 2);
+                        let pits = fbm(u, v, 8, 2, 3);
+                        (base * 0.7 + pits * 0.3).powf(1.5)
+                    }
+                    Paper::Canvas => {
+                        let period = 8.0;
+                        let warp = (u * std::f64::consts::TAU / period).sin();
+                        let weft = (v * std::f64::consts::TAU / period).sin();
+                        let weave = 0.5 + 0.25 * (warp + weft);
+                        weave * 0.8 + fbm(u, v, 16, 2, 4) * 0.2
+                    }
+                    Paper::Laid => {
+                        let lines = 0.5 + 0.5 * (v * std::f64::consts::TAU / 4.0).cos();
+                        let chains = if x % 64 < 2 { 0.3 } else { 1.0 };
+                        (lines * 0.4 + fbm(u, v, 32, 3, 5) * 0.6) * chains
+                    }
+                };
+                data.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        }
+        GrayImage::from_data(size, size, data).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GrainSource {
+    Paper(Paper),
+    /// Id of an imported grain in `Resources`.
+    Imported(u32),
+}
+
+/// Texture modulating the opacity of dabs, tiled in canvas space.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Grain {
+    pub source: GrainSource,
+    pub scale: f64,
+    pub contrast: f64,
+    pub invert: bool,
+}
+
+impl Grain {
+    pub fn new(source: GrainSource) -> Self {
+        Self {
+            source,
+            scale: 1.0,
+            contrast: 1.0,
+            invert: false,
+        }
+    }
+
+    /// Paint coverage for every texel after contrast and inversion.
+    pub fn coverage(&self, texture: &GrayImage) -> GrayImage {
+        let data = texture
+            .data
+            .iter()
+            .map(|&v| {
+                let v = v as f64 / 255.0;
+                let v = if self.invert { 1.0 - v } else { v };
+                let v = (v - 0.5) * self.contrast + 0.5;
+                (v.clamp(0.0, 1.0) * 255.0).round() as u8
+            })
+            .collect();
+        GrayImage::from_data(texture.width, texture.height, data).unwrap()
+    }
+}
+
+/// Tileable fractal value noise in `0.0..=1.0`, `period` is the lattice cell of the first octave.
+fn fbm(x: f64, y: f64, period: u32, octaves: u32, seed: u32) -> f64 {
+    let mut sum = 0.0;
+    let mut amplitude = 0.5;
+    let mut total = 0.0;
+    let mut period = period.max(1);
+    for octave in 0..octaves {
+        sum += amplitude * value_noise(x, y, period, seed.wrapping_add(octave * 7919));
+        total += amplitude;
+        amplitude *= 0.5;
+        period = (period / 2).max(1);
+    }
+    sum / total
+}
+
+fn value_noise(x: f64, y: f64, period: u32, seed: u32) -> f64 {
+    let cells = (PAPER_SIZE / period).max(1);
+    let (fx, fy) = (x / period as f64, y / period as f64);
+    let (x0, y0) = (fx.floor() as u32, fy.floor() as u32);
+    let (tx, ty) = (smoothstep(fx.fract()), smoothstep(fy.fract()));
+
+    let at = |cx: u32, cy: u32| lattice(cx % cells, cy % cells, seed);
+
+    let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+    let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+    top + (bottom - top) * ty
+}
+
+fn lattice(x: u32, y: u32, seed: u32) -> f64 {
+    let mut h = x
+        .wrapping_mul(374_761_393)
+        .wrapping_add(y.wrapping_mul(668_265_263))
+        .wrapping_add(seed.wrapping_mul(2_147_483_647));
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+    h as f64 / u32::MAX as f64
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vector::{Rectangle, Vector2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// Infinite line across the document, pulled out of a ruler.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Guide {
+    pub orientation: Orientation,
+    /// Document y of horizontal guides, x of vertical ones.
+    pub position: f64,
+}
+
+impl Guide {
+    pub fn new(orientation: Orientation, position: f64) -> Self {
+        Self {
+            orientation,
+            position,
+        }
+    }
+
+    /// Moves the guide through `point`.
+    pub fn move_to(&mut self, point: Vector2) {
+        self.position = match self.orientation {
+            Orientation::Horizontal => point.y,
+            Orientation::Vertical => point.x,
+        };
+    }
+
+    pub fn distance(&self, point: Vector2) -> f64 {
+        match self.orientation {
+            Orientation::Horizontal => (point.y - self.position).abs(),
+            Orientation::Vertical => (point.x - self.position).abs(),
+        }
+    }
+
+    /// Segment of the guide inside the rectangle from `min` to `max`.
+    pub fn segment(&self, min: Vector2, max: Vector2) -> [Vector2; 2] {
+        match self.orientation {
+            Orientation::Horizontal => [
+                Vector2::new(min.x, self.position),
+                Vector2::new(max.x, self.position),
+            ],
+            Orientation::Vertical => [
+                Vector2::new(self.position, min.y),
+                Vector2::new(self.position, max.y),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GuideSettings {
+    pub rulers: bool,
+    pub grid: bool,
+    /// Distance between grid lines in document pixels.
+    pub grid_spacing: f64,
+    pub snap_to_guides: bool,
+    pub snap_to_grid: bool,
+    pub snap_to_edges: bool,
+}
+
+impl Default for GuideSettings {
+    fn default() -> Self {
+        Self {
+            rulers: true,
+            grid: false,
+            grid_spacing: 50.0,
+            snap_to_guides: true,
+            snap_to_grid: false,
+            snap_to_edges: true,
+        }
+    }
+}
+
+/// Lines of the document points snap to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapLines {
+    /// Positions of vertical lines.
+    pub xs: Vec<f64>,
+    /// Positions of horizontal lines.
+    pub ys: Vec<f64>,
+    /// Spacing of the grid, if it's snapped to.
+    pub grid: Option<f64>,
+}
+
+impl SnapLines {
+    pub fn new(settings: &GuideSettings, guides: &[Guide], document: Rectangle) -> Self {
+        let mut lines = SnapLines::default();
+        if settings.snap_to_guides {
+            for guide in guides {
+                match guide.orientation {
+                    Orientation::Horizontal => lines.ys.push(guide.position),
+                    Orientation::Vertical => lines.xs.push(guide.position),
+                }
+            }
+        }
+        if settings.snap_to_edges {
+            let (min, max) = (document.coord, document.coord + document.size);
+            lines.xs.extend([min.x, max.x]);
+            lines.ys.extend([min.y, max.y]);
+        }
+        if settings.snap_to_grid && settings.grid_spacing > 0.0 {
+            lines.grid = Some(settings.grid_spacing);
+        }
+        lines
+    }
+
+    /// Moves each coordinate of `point` to the closest line within `tolerance`.
+    pub fn snap(&self, point: Vector2, tolerance: f64) -> Vector2 {
+        let grid = |value: f64| self.grid.map(|spacing| (value / spacing).round() * spacing);
+        Vector2::new(
+            snap_value(
+                point.x,
+                self.xs.iter().copied().chain(grid(point.x)),
+                tolerance,
+            ),
+            snap_value(
+                point.y,
+                self.ys.iter().copied().chain(grid(point.y)),
+                tolerance,
+            ),
+        )
+    }
+}
+
+fn snap_value(value: f64, candidates: impl Iterator<Item = f64>, tolerance: f64) -> f64 {
+    candidates
+        .map(|candidate| (candidate, (candidate - value).abs()))
+        .filter(|(_, distance)| *distance <= tolerance)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(value, |(candidate, _)| candidate)
+}
+
+/// Distance between labeled ruler ticks in document pixels: 1, 2 or 5 times a power
+/// of ten at least `min_spacing` long.
+pub fn ruler_step(min_spacing: f64) -> f64 {
+    let power = 10f64.powf(min_spacing.max(f64::MIN_POSITIVE).log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|k| k * power)
+        .find(|&step| step >= min_spacing)
+        .unwrap_or(power * 10.0)
+}
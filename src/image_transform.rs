@@ -0,0 +1,273 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    document::MAX_SIDE,
+    raster::RgbaImage,
+    vector::{Transform, Vector2},
+};
+
+/// Rotations by right angles and flips, which move pixels without resampling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transformation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+}
+
+impl Transformation {
+    pub const ALL: [Transformation; 5] = [
+        Transformation::Rotate90,
+        Transformation::Rotate270,
+        Transformation::Rotate180,
+        Transformation::FlipHorizontal,
+        Transformation::FlipVertical,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Transformation::Rotate90 => "Rotate 90° clockwise",
+            Transformation::Rotate180 => "Rotate 180°",
+            Transformation::Rotate270 => "Rotate 90° counterclockwise",
+            Transformation::FlipHorizontal => "Flip horizontally",
+            Transformation::FlipVertical => "Flip vertically",
+        }
+    }
+
+    /// Whether width and height trade places.
+    pub fn swaps_sides(self) -> bool {
+        matches!(self, Transformation::Rotate90 | Transformation::Rotate270)
+    }
+
+    /// Size of an image of `size` once transformed.
+    pub fn size(self, size: (u32, u32)) -> (u32, u32) {
+        if self.swaps_sides() {
+            (size.1, size.0)
+        } else {
+            size
+        }
+    }
+
+    /// The transformation around the origin, y pointing down.
+    pub fn linear(self) -> Transform {
+        match self {
+            Transformation::Rotate90 => Transform::rotate(FRAC_PI_2),
+            Transformation::Rotate180 => Transform::rotate(PI),
+            Transformation::Rotate270 => Transform::rotate(-FRAC_PI_2),
+            Transformation::FlipHorizontal => Transform::scale(-1.0, 1.0),
+            Transformation::FlipVertical => Transform::scale(1.0, -1.0),
+        }
+    }
+}
+
+/// Applies `linear` around the middle of an image of size `from`, placing the
+/// result in the middle of an image of size `to`.
+pub fn about_centers(linear: Transform, from: (u32, u32), to: (u32, u32)) -> Transform {
+    let middle = |(w, h): (u32, u32)| Vector2::new(w as f64 / 2.0, h as f64 / 2.0);
+    let transform = Transform::translate(middle(from) * -1.0)
+        .then(linear)
+        .then(Transform::translate(middle(to)));
+    // Right angles shouldn't leave float noise in the coefficients
+    let clean = |v: f64| {
+        if (v - v.round()).abs() < 1e-9 {
+            v.round()
+        } else {
+            v
+        }
+    };
+    Transform {
+        a: clean(transform.a),
+        b: clean(transform.b),
+        c: clean(transform.c),
+        d: clean(transform.d),
+        e: clean(transform.e),
+        f: clean(transform.f),
+    }
+}
+
+/// Size of the bounding box of an image of `size` rotated by `angle`.
+pub fn rotated_size(size: (u32, u32), angle: f64) -> (u32, u32) {
+    let (sin, cos) = angle.sin_cos();
+    let (w, h) = (size.0 as f64, size.1 as f64);
+    let side = |v: f64| ((v - 1e-9).ceil() as u32).clamp(1, MAX_SIDE);
+    (
+        side(w * cos.abs() + h * sin.abs()),
+        side(w * sin.abs() + h * cos.abs()),
+    )
+}
+
+/// Maps the image through `transform` onto a `width` by `height` image, parts
+/// falling outside are cut off and uncovered pixels stay transparent.
+///
+/// Bilinear `smooth` sampling is for arbitrary angles, right angles and flips
+/// copy pixels exactly without it.
+pub fn transform_image(
+    image: &RgbaImage,
+    transform: &Transform,
+    width: u32,
+    height: u32,
+    smooth: bool,
+) -> RgbaImage {
+    let mut result = RgbaImage::new(width, height);
+    let inverse = match transform.inverse() {
+        Some(inverse) => inverse,
+        None => return result,
+    };
+    for y in 0..height {
+        for x in 0..width {
+            let source = inverse.apply(Vector2::new(x as f64 + 0.5, y as f64 + 0.5));
+            let pixel = if smooth {
+                sample_bilinear(image, source)
+            } else {
+                sample_nearest(image, source)
+            };
+            if let Some(pixel) = pixel {
+                let i = ((y * width + x) * 4) as usize;
+                result.data[i..i + 4].copy_from_slice(&pixel);
+            }
+        }
+    }
+    result
+}
+
+fn sample_nearest(image: &RgbaImage, point: Vector2) -> Option<[u8; 4]> {
+    let (x, y) = (point.x.floor(), point.y.floor());
+    if x < 0.0 || y < 0.0 || x >= image.width as f64 || y >= image.height as f64 {
+        return None;
+    }
+    let i = ((y as u32 * image.width + x as u32) * 4) as usize;
+    image.data[i..i + 4].try_into().ok()
+}
+
+/// Interpolates premultiplied colors of the four pixels around `point`, pixels past
+/// the edges are transparent.
+fn sample_bilinear(image: &RgbaImage, point: Vector2) -> Option<[u8; 4]> {
+    let (x, y) = (point.x - 0.5, point.y - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let mut sum = [0.0; 4];
+    for (dx, dy, weight) in [
+        (0.0, 0.0, (1.0 - fx) * (1.0 - fy)),
+        (1.0, 0.0, fx * (1.0 - fy)),
+        (0.0, 1.0, (1.0 - fx) * fy),
+        (1.0, 1.0, fx * fy),
+    ] {
+        let (px, py) = (x0 + dx, y0 + dy);
+        if weight == 0.0
+            || px < 0.0
+            || py < 0.0
+            || px >= image.width as f64
+            || py >= image.height as f64
+        {
+            continue;
+        }
+        let i = ((py as u32 * image.width + px as u32) * 4) as usize;
+        let alpha = image.data[i + 3] as f64 / 255.0;
+        for (c, value) in sum[..3].iter_mut().enumerate() {
+            *value += image.data[i + c] as f64 / 255.0 * alpha * weight;
+        }
+        sum[3] += alpha * weight;
+    }
+    if sum[3] <= 0.0 {
+        return None;
+    }
+    let alpha = sum[3].min(1.0);
+    Some([
+        (sum[0] / sum[3] * 255.0).round() as u8,
+        (sum[1] / sum[3] * 255.0).round() as u8,
+        (sum[2] / sum[3] * 255.0).round() as u8,
+        (alpha * 255.0).round() as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn gray(value: u8) -> Color {
+        Color::new(value, value, value, 255)
+    }
+
+    /// 3×2 image with distinct pixels.
+    fn pattern() -> RgbaImage {
+        let mut image = RgbaImage::new(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                image.set(x, y, gray((y * 3 + x) as u8 * 10));
+            }
+        }
+        image
+    }
+
+    fn apply(image: &RgbaImage, transformation: Transformation) -> RgbaImage {
+        let size = (image.width, image.height);
+        let (width, height) = transformation.size(size);
+        let transform = about_centers(transformation.linear(), size, (width, height));
+        transform_image(image, &transform, width, height, false)
+    }
+
+    fn values(image: &RgbaImage) -> Vec<Vec<u8>> {
+        (0..image.height)
+            .map(|y| (0..image.width).map(|x| image.get(x, y).get_r()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn rotates_clockwise() {
+        let result = apply(&pattern(), Transformation::Rotate90);
+        assert_eq!(values(&result), [[30, 0], [40, 10], [50, 20]]);
+    }
+
+    #[test]
+    fn rotates_counterclockwise() {
+        let result = apply(&pattern(), Transformation::Rotate270);
+        assert_eq!(values(&result), [[20, 50], [10, 40], [0, 30]]);
+    }
+
+    #[test]
+    fn rotates_half_turn() {
+        let result = apply(&pattern(), Transformation::Rotate180);
+        assert_eq!(values(&result), [[50, 40, 30], [20, 10, 0]]);
+    }
+
+    #[test]
+    fn flips() {
+        let result = apply(&pattern(), Transformation::FlipHorizontal);
+        assert_eq!(values(&result), [[20, 10, 0], [50, 40, 30]]);
+        let result = apply(&pattern(), Transformation::FlipVertical);
+        assert_eq!(values(&result), [[30, 40, 50], [0, 10, 20]]);
+    }
+
+    #[test]
+    fn four_quarter_turns_are_identity() {
+        let mut image = pattern();
+        for _ in 0..4 {
+            image = apply(&image, Transformation::Rotate90);
+        }
+        assert_eq!(image, pattern());
+    }
+
+    #[test]
+    fn rotated_size_fits_corners() {
+        assert_eq!(rotated_size((100, 50), 0.0), (100, 50));
+        assert_eq!(rotated_size((100, 50), FRAC_PI_2), (50, 100));
+        assert_eq!(rotated_size((100, 100), PI / 4.0), (142, 142));
+    }
+
+    #[test]
+    fn smooth_rotation_keeps_uniform_color_inside() {
+        let mut image = RgbaImage::new(20, 20);
+        for y in 0..20 {
+            for x in 0..20 {
+                image.set(x, y, gray(200));
+            }
+        }
+        let transform = about_centers(Transform::rotate(0.3), (20, 20), (20, 20));
+        let result = transform_image(&image, &transform, 20, 20, true);
+        assert_eq!(result.get(10, 10), gray(200));
+        // Corners rotate out of the image
+        assert_eq!(result.get(0, 0).get_a(), 0);
+    }
+}
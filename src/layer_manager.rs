@@ -2,7 +2,19 @@ use std::{cell::RefCell, ops::Deref, rc::Rc};
 
 use web_sys::HtmlCanvasElement;
 
-use crate::virtual_context::VirtualContext;
+use crate::{
+    color::Color,
+    document::DocumentSettings,
+    files::canvas_to_png,
+    guides::{Guide, Orientation},
+    image_transform::transform_image,
+    png,
+    raster::RgbaImage,
+    resample::{resample, Filter},
+    tiles::{tile_aligned, Tile, TileGrid, TILE_SIZE},
+    vector::{Rectangle, Transform, Vector2},
+    virtual_context::{create_canvas_2d, VirtualContext},
+};
 
 pub struct RcLayerManager(pub Rc<RefCell<LayerManager>>);
 
@@ -32,13 +44,35 @@ impl PartialEq for RcLayerManager {
     }
 }
 
+/// Layers of a new document.
+const INITIAL_LAYERS: usize = 5;
+
+/// Keyword of the PNG text chunk holding the guides of an exported document.
+const GUIDES_KEYWORD: &str = "paint_wasm.guides";
+
+/// Largest width or height infinite documents grow to.
+const MAX_EXTENT: f64 = 16384.0;
+
 pub struct LayerManager {
     layers: Vec<Layer>,
     next_id: usize,
     selected: Option<usize>,
+    /// Pixels of the selected layer around what is being painted, loaded from its
+    /// tiles and stored back into them.
+    work: VirtualContext,
 
     width: u32,
     height: u32,
+    /// Document position of the top left pixel of every layer, only infinite
+    /// documents move it.
+    origin: Vector2,
+    /// Layers grow when painted past their edges.
+    infinite: bool,
+    /// Color new areas of the bottom layer get in infinite documents.
+    background: Option<Color>,
+    /// Pixels per inch.
+    resolution: u32,
+    guides: Vec<Guide>,
 
     subscribers: Vec<Subscriber>,
     next_subscriber_id: usize,
@@ -50,16 +84,52 @@ impl LayerManager {
             layers: vec![],
             next_id: 0,
             selected: None,
+            work: VirtualContext::new_independent(TILE_SIZE, TILE_SIZE),
             width,
             height,
+            origin: Vector2::new(0.0, 0.0),
+            infinite: false,
+            background: None,
+            resolution: 72,
+            guides: vec![],
             subscribers: vec![],
             next_subscriber_id: 0,
         }
     }
 
+    /// Document with the background in the bottom layer and empty layers above it.
+    pub fn from_settings(settings: &DocumentSettings) -> Self {
+        let mut manager = Self::new(settings.width, settings.height);
+        manager.resolution = settings.resolution;
+        manager.infinite = settings.infinite;
+        manager.background = settings.background.color();
+        for _ in 0..INITIAL_LAYERS {
+            manager.push_layer();
+        }
+        if let Some(color) = manager.background {
+            let bounds = manager.bounds();
+            manager.layers[0].tiles.fill(bounds, color);
+        }
+        manager
+    }
+
+    /// Document opened from an exported PNG decoded as `image`, with the guides
+    /// stored in the file.
+    pub fn from_png(png: &[u8], image: &RgbaImage) -> Self {
+        let mut manager = Self::new(image.width, image.height);
+        for _ in 0..INITIAL_LAYERS {
+            manager.push_layer();
+        }
+        manager.layers[0].tiles = TileGrid::from_image(image, Vector2::new(0.0, 0.0));
+        manager.guides = png::read_text(png, GUIDES_KEYWORD)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        manager
+    }
+
     pub fn push_layer(&mut self) {
         let id = self.next_id();
-        self.layers.push(Layer::new(id, self.width, self.height));
+        self.layers.push(Layer::new(id));
     }
 
     pub fn get_layer(&self, id: usize) -> Option<&'_ Layer> {
@@ -71,13 +141,378 @@ impl LayerManager {
         None
     }
 
-    pub fn draw_in_context<F: FnMut(&VirtualContext)>(&self, mut f: F) {
-        if let Some(layer) = self.get_selected() {
-            f(&layer.context);
+    /// Paints on the selected layer, `area` holds everything `f` changes.
+    ///
+    /// Only the tiles under `area` are loaded for `f` to paint on, parts of the layer
+    /// outside `bounds()` stay as they are.
+    pub fn draw_in_context<F: FnMut(&VirtualContext)>(&mut self, area: Rectangle, mut f: F) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        let bounds = self.bounds();
+        let (area, region) = match area.intersection(bounds) {
+            Some(area) => (area, tile_aligned(area).intersection(bounds).unwrap()),
+            None => return,
+        };
+        let (width, height) = (region.size.x as u32, region.size.y as u32);
+        if (self.work.width(), self.work.height()) != (width, height) {
+            self.work.set_size(width, height);
+        }
+        self.work.set_origin(region.coord);
+        self.work.clear(Color::TRANSPARENT);
+        let layer = &mut self.layers[selected];
+        self.work.draw_images_bounded(
+            layer
+                .tiles
+                .iter()
+                .filter(|(_, rect)| rect.intersection(region).is_some())
+                .map(|(tile, rect)| (tile.get_canvas(), rect)),
+        );
+
+        f(&self.work);
+
+        layer
+            .tiles
+            .store(self.work.get_canvas(), region.coord, area);
+        let id = layer.id;
+        self.notify(Notification::Change { id });
+    }
+
+    pub fn selected_image(&self) -> Option<RgbaImage> {
+        self.get_selected()
+            .map(|layer| layer.tiles.to_image(self.bounds()))
+    }
+
+    /// Tiles of the selected layer, cheap to keep as they are shared until painted.
+    pub fn selected_tiles(&self) -> Option<TileGrid> {
+        self.get_selected().map(|layer| layer.tiles.clone())
+    }
+
+    /// Brings the selected layer back to tiles taken from `selected_tiles`.
+    pub fn restore_selected(&mut self, tiles: TileGrid) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        self.layers[selected].tiles = tiles;
+        let id = self.layers[selected].id;
+        self.notify(Notification::Change { id });
+    }
+
+    /// Gives tiles of the selected layer kept from before `reserve` grew the document
+    /// from `bounds` what the layer got in the new area.
+    pub fn grow_selected_tiles(&self, tiles: &mut TileGrid, bounds: Rectangle) {
+        if self.selected == Some(0) {
+            self.fill_background(tiles, bounds);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Part of the document covered by the layers.
+    pub fn bounds(&self) -> Rectangle {
+        Rectangle::new(
+            self.origin.x,
+            self.origin.y,
+            self.width as f64,
+            self.height as f64,
+        )
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.infinite
+    }
+
+    /// Grows the layers of an infinite document tile by tile to cover `area`, returns
+    /// the bounds from before if they did.
+    pub fn reserve(&mut self, area: Rectangle) -> Option<Rectangle> {
+        if !self.infinite {
+            return None;
+        }
+        let bounds = self.bounds();
+        let (area_max, bounds_max) = (area.coord + area.size, bounds.coord + bounds.size);
+        let tile = TILE_SIZE as f64;
+        // Only the edges the area passes move, onto the tile grid
+        let lower = |area: f64, edge: f64| {
+            if area < edge {
+                (area / tile).floor() * tile
+            } else {
+                edge
+            }
+        };
+        let upper = |area: f64, edge: f64| {
+            if area > edge {
+                (area / tile).ceil() * tile
+            } else {
+                edge
+            }
+        };
+        let min = Vector2::new(
+            lower(area.coord.x, bounds.coord.x),
+            lower(area.coord.y, bounds.coord.y),
+        );
+        let max = Vector2::new(
+            upper(area_max.x, bounds_max.x),
+            upper(area_max.y, bounds_max.y),
+        );
+        let size = max - min;
+        if (min == bounds.coord && size == bounds.size)
+            || size.x > MAX_EXTENT
+            || size.y > MAX_EXTENT
+        {
+            return None;
+        }
+
+        self.origin = min;
+        self.width = size.x as u32;
+        self.height = size.y as u32;
+        if !self.layers.is_empty() {
+            let mut bottom = std::mem::take(&mut self.layers[0].tiles);
+            self.fill_background(&mut bottom, bounds);
+            self.layers[0].tiles = bottom;
+        }
+
+        for layer in self.layers.iter() {
+            self.notify(Notification::Change { id: layer.id });
+        }
+        Some(bounds)
+    }
+
+    /// Fills what lies outside `bounds` in the bounds of the layers with the background
+    /// of infinite documents.
+    fn fill_background(&self, tiles: &mut TileGrid, bounds: Rectangle) {
+        let color = match self.background {
+            Some(color) => color,
+            None => return,
+        };
+        let (min, max) = (self.origin, self.origin + self.bounds().size);
+        let (old_min, old_max) = (bounds.coord, bounds.coord + bounds.size);
+        for rect in [
+            Rectangle::new(min.x, min.y, max.x - min.x, old_min.y - min.y),
+            Rectangle::new(min.x, old_max.y, max.x - min.x, max.y - old_max.y),
+            Rectangle::new(min.x, old_min.y, old_min.x - min.x, bounds.size.y),
+            Rectangle::new(old_max.x, old_min.y, max.x - old_max.x, bounds.size.y),
+        ] {
+            if rect.size.x > 0.0 && rect.size.y > 0.0 {
+                tiles.fill(rect, color);
+            }
+        }
+    }
+
+    /// Moves the document so that the layers start at zero again, returns how far
+    /// document points moved. Operations changing the canvas size expect it.
+    pub fn reset_origin(&mut self) -> Vector2 {
+        let shift = self.origin * -1.0;
+        if shift == Vector2::new(0.0, 0.0) {
+            return shift;
+        }
+        // The origin only ever moves by whole tiles
+        for layer in self.layers.iter_mut() {
+            layer.tiles.shift(shift);
+            layer.last_point = layer.last_point.map(|p| p + shift);
+        }
+        for guide in self.guides.iter_mut() {
+            guide.position += match guide.orientation {
+                Orientation::Horizontal => shift.y,
+                Orientation::Vertical => shift.x,
+            };
+        }
+        self.origin = Vector2::new(0.0, 0.0);
+        shift
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Scales every layer to the new size, guides and stroke ends move along.
+    pub fn scale(&mut self, width: u32, height: u32, filter: Filter) {
+        let factor = Vector2::new(
+            width as f64 / self.width as f64,
+            height as f64 / self.height as f64,
+        );
+        let bounds = self.bounds();
+        for layer in self.layers.iter_mut() {
+            let image = resample(&layer.tiles.to_image(bounds), width, height, filter);
+            layer.tiles = TileGrid::from_image(&image, self.origin);
+            layer.last_point = layer
+                .last_point
+                .map(|p| Vector2::new(p.x * factor.x, p.y * factor.y));
+        }
+        for guide in self.guides.iter_mut() {
+            guide.position *= match guide.orientation {
+                Orientation::Horizontal => factor.y,
+                Orientation::Vertical => factor.x,
+            };
+        }
+        self.width = width;
+        self.height = height;
+
+        for layer in self.layers.iter() {
             self.notify(Notification::Change { id: layer.id });
         }
     }
 
+    /// Changes the canvas size without scaling, the content moves by `offset`.
+    pub fn resize_canvas(&mut self, width: u32, height: u32, offset: Vector2) {
+        let area = Rectangle::new(
+            self.origin.x - offset.x,
+            self.origin.y - offset.y,
+            width as f64,
+            height as f64,
+        );
+        for layer in self.layers.iter_mut() {
+            let image = layer.tiles.to_image(area);
+            layer.tiles = TileGrid::from_image(&image, self.origin);
+            layer.last_point = layer.last_point.map(|p| p + offset);
+        }
+        for guide in self.guides.iter_mut() {
+            guide.position += match guide.orientation {
+                Orientation::Horizontal => offset.y,
+                Orientation::Vertical => offset.x,
+            };
+        }
+        self.width = width;
+        self.height = height;
+
+        for layer in self.layers.iter() {
+            self.notify(Notification::Change { id: layer.id });
+        }
+    }
+
+    /// Maps every layer through `transform` onto a canvas of the new size. Stroke ends
+    /// move along, guides pass through their moved middle along the closest axis.
+    pub fn transform(&mut self, transform: &Transform, width: u32, height: u32, smooth: bool) {
+        let bounds = self.bounds();
+        for layer in self.layers.iter_mut() {
+            let image = layer.tiles.to_image(bounds);
+            let image = transform_image(&image, transform, width, height, smooth);
+            layer.tiles = TileGrid::from_image(&image, self.origin);
+            layer.last_point = layer.last_point.map(|p| transform.apply(p));
+        }
+        let middle = Vector2::new(self.width as f64 / 2.0, self.height as f64 / 2.0);
+        let origin = transform.apply(Vector2::new(0.0, 0.0));
+        for guide in self.guides.iter_mut() {
+            let (point, direction) = match guide.orientation {
+                Orientation::Horizontal => (
+                    Vector2::new(middle.x, guide.position),
+                    Vector2::new(1.0, 0.0),
+                ),
+                Orientation::Vertical => (
+                    Vector2::new(guide.position, middle.y),
+                    Vector2::new(0.0, 1.0),
+                ),
+            };
+            let direction = transform.apply(direction) - origin;
+            guide.orientation = if direction.x.abs() >= direction.y.abs() {
+                Orientation::Horizontal
+            } else {
+                Orientation::Vertical
+            };
+            guide.move_to(transform.apply(point));
+        }
+        self.width = width;
+        self.height = height;
+
+        for layer in self.layers.iter() {
+            self.notify(Notification::Change { id: layer.id });
+        }
+    }
+
+    /// Maps the selected layer through `transform`, keeping the canvas size.
+    pub fn transform_selected(&mut self, transform: &Transform, smooth: bool) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        let (width, height) = (self.width, self.height);
+        let bounds = self.bounds();
+        let layer = &mut self.layers[selected];
+        let image = layer.tiles.to_image(bounds);
+        let image = transform_image(&image, transform, width, height, smooth);
+        layer.tiles = TileGrid::from_image(&image, self.origin);
+        layer.last_point = layer.last_point.map(|p| transform.apply(p));
+        let id = layer.id;
+        self.notify(Notification::Change { id });
+    }
+
+    /// All layers composited into a canvas of `width` by `height` pixels showing the
+    /// layer pixels from `(x, y)` on.
+    pub fn flatten(&self, x: u32, y: u32, width: u32, height: u32) -> HtmlCanvasElement {
+        let (canvas, context) = create_canvas_2d();
+        canvas.set_width(width);
+        canvas.set_height(height);
+        let offset = self.origin + Vector2::new(x as f64, y as f64);
+        for (tile, rect) in self.layers.iter().flat_map(Layer::tiles) {
+            context
+                .draw_image_with_html_canvas_element(
+                    tile.get_canvas(),
+                    rect.coord.x - offset.x,
+                    rect.coord.y - offset.y,
+                )
+                .unwrap();
+        }
+        canvas
+    }
+
+    /// All layers composited into a PNG that keeps the guides, infinite documents are
+    /// cropped to what was painted.
+    pub fn export_png(&self) -> Vec<u8> {
+        let whole = (0, 0, self.width, self.height);
+        let (x, y, width, height) = if self.infinite {
+            self.trim_bounds().unwrap_or(whole)
+        } else {
+            whole
+        };
+        let png = canvas_to_png(&self.flatten(x, y, width, height));
+
+        // Stored relative to the top left pixel of the image
+        let offset = self.origin + Vector2::new(x as f64, y as f64);
+        let guides: Vec<Guide> = self
+            .guides
+            .iter()
+            .map(|&guide| {
+                let mut guide = guide;
+                guide.position -= match guide.orientation {
+                    Orientation::Horizontal => offset.y,
+                    Orientation::Vertical => offset.x,
+                };
+                guide
+            })
+            .collect();
+        let json = serde_json::to_string(&guides).unwrap();
+        png::add_text(&png, GUIDES_KEYWORD, &json).unwrap_or(png)
+    }
+
+    /// Smallest rectangle `(x, y, width, height)` holding the content of every layer,
+    /// ignoring transparent or uniformly colored borders.
+    pub fn trim_bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        let bounds = self.bounds();
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.tiles.to_image(bounds).content_bounds())
+            .map(|(x, y, w, h)| (x, y, x + w, y + h))
+            .reduce(|(ax0, ay0, ax1, ay1), (bx0, by0, bx1, by1)| {
+                (ax0.min(bx0), ay0.min(by0), ax1.max(bx1), ay1.max(by1))
+            })
+            .map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0, y1 - y0))
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn guides(&self) -> &[Guide] {
+        &self.guides
+    }
+
+    pub fn guides_mut(&mut self) -> &mut Vec<Guide> {
+        &mut self.guides
+    }
+
     pub fn iter_layers(&self) -> impl DoubleEndedIterator<Item = &Layer> + '_ {
         self.layers.iter()
     }
@@ -88,6 +523,10 @@ impl LayerManager {
         id
     }
 
+    pub fn unsubscribe(&mut self, id: usize) {
+        self.subscribers.retain(|subscriber| subscriber.id != id);
+    }
+
     pub fn get_selected(&self) -> Option<&'_ Layer> {
         if let Some(selected) = self.selected {
             return self.get_layer(selected);
@@ -103,6 +542,13 @@ impl LayerManager {
         self.layers[id].is_selected = true;
     }
 
+    /// Remembers where the last stroke on the selected layer ended.
+    pub fn set_last_point(&mut self, point: Vector2) {
+        if let Some(selected) = self.selected {
+            self.layers[selected].last_point = Some(point);
+        }
+    }
+
     fn notify(&self, notification: Notification) {
         for subscriber in self.subscribers.iter() {
             (subscriber.callback)(notification);
@@ -124,16 +570,18 @@ impl LayerManager {
 
 pub struct Layer {
     id: usize,
-    context: VirtualContext,
+    tiles: TileGrid,
     is_selected: bool,
+    last_point: Option<Vector2>,
 }
 
 impl Layer {
-    fn new(id: usize, width: u32, height: u32) -> Self {
+    fn new(id: usize) -> Self {
         Self {
             id,
-            context: VirtualContext::new_independent(width, height),
+            tiles: TileGrid::default(),
             is_selected: false,
+            last_point: None,
         }
     }
 
@@ -145,19 +593,28 @@ impl Layer {
         self.id
     }
 
-    pub fn get_canvas(&self) -> &'_ HtmlCanvasElement {
-        self.context.get_canvas()
+    pub fn get_last_point(&self) -> Option<Vector2> {
+        self.last_point
+    }
+
+    pub fn grid(&self) -> &TileGrid {
+        &self.tiles
+    }
+
+    /// Painted tiles with the document area of each, empty ones aren't there to draw.
+    pub fn tiles(&self) -> impl Iterator<Item = (&Tile, Rectangle)> + '_ {
+        self.tiles.iter()
     }
 }
 
 struct Subscriber {
-    _id: usize,
+    id: usize,
     callback: Box<dyn Fn(Notification)>,
 }
 
 impl Subscriber {
     fn new(id: usize, callback: Box<dyn Fn(Notification)>) -> Self {
-        Self { _id: id, callback }
+        Self { id, callback }
     }
 }
 
@@ -1,8 +1,31 @@
+pub mod airbrush;
+pub mod assistants;
+pub mod base64;
+pub mod brush;
+pub mod brush_presets;
 pub mod color;
 pub mod components;
+pub mod crop;
+pub mod document;
+pub mod dodge_burn;
+pub mod files;
+pub mod gimp_formats;
+pub mod grain;
+pub mod gpu;
+pub mod guides;
+pub mod image_transform;
 pub mod layer_manager;
+pub mod png;
+pub mod raster;
+pub mod redraw;
+pub mod resample;
+pub mod resources;
 pub mod shaders;
+pub mod shape_recognition;
+pub mod tiles;
+pub mod tool;
 pub mod vector;
+pub mod viewport;
 pub mod virtual_context;
 
 fn main() {
@@ -0,0 +1,121 @@
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Copy of the PNG with a `tEXt` chunk added after the header, `None` if `png`
+/// isn't one. The chunk holds the keyword, a zero byte and the text.
+pub fn add_text(png: &[u8], keyword: &str, text: &str) -> Option<Vec<u8>> {
+    let (kind, _, header_end) = chunks(png).next()?;
+    if kind != *b"IHDR" {
+        return None;
+    }
+    let mut data = keyword.as_bytes().to_vec();
+    data.push(0);
+    data.extend_from_slice(text.as_bytes());
+
+    let mut result = png[..header_end].to_vec();
+    result.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = result.len();
+    result.extend_from_slice(b"tEXt");
+    result.extend_from_slice(&data);
+    let crc = crc32(&result[start..]);
+    result.extend_from_slice(&crc.to_be_bytes());
+    result.extend_from_slice(&png[header_end..]);
+    Some(result)
+}
+
+/// Text of the first `tEXt` chunk with the keyword.
+pub fn read_text(png: &[u8], keyword: &str) -> Option<String> {
+    chunks(png)
+        .filter(|(kind, _, _)| kind == b"tEXt")
+        .find_map(|(_, data, _)| {
+            let separator = data.iter().position(|&b| b == 0)?;
+            (&data[..separator] == keyword.as_bytes())
+                .then(|| String::from_utf8_lossy(&data[separator + 1..]).into_owned())
+        })
+}
+
+/// Type, data and end offset of every complete chunk, nothing if the signature is
+/// missing.
+///
+/// A PNG is an 8 byte signature followed by chunks: a big-endian data length, a four
+/// letter type, the data and a CRC of type and data.
+fn chunks(png: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8], usize)> {
+    let mut offset = if png.starts_with(SIGNATURE) {
+        SIGNATURE.len()
+    } else {
+        png.len()
+    };
+    std::iter::from_fn(move || {
+        let length = u32::from_be_bytes(png.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let kind: [u8; 4] = png.get(offset + 4..offset + 8)?.try_into().ok()?;
+        let data_end = (offset + 8).checked_add(length)?;
+        let data = png.get(offset + 8..data_end)?;
+        offset = data_end.checked_add(4).filter(|&end| end <= png.len())?;
+        Some((kind, data, offset))
+    })
+}
+
+/// CRC-32 as used by PNG and zlib.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signature, a 1×1 header and an empty image end, enough for the chunk layout.
+    fn minimal_png() -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        for (kind, data) in [
+            (b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0][..]),
+            (b"IEND", &[][..]),
+        ] {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            let mut typed = kind.to_vec();
+            typed.extend_from_slice(data);
+            png.extend_from_slice(&typed);
+            png.extend_from_slice(&crc32(&typed).to_be_bytes());
+        }
+        png
+    }
+
+    #[test]
+    fn crc_matches_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let png = add_text(&minimal_png(), "guides", "[1,2]").unwrap();
+        assert_eq!(read_text(&png, "guides").as_deref(), Some("[1,2]"));
+        assert_eq!(read_text(&png, "other"), None);
+
+        let kinds: Vec<[u8; 4]> = chunks(&png).map(|(kind, _, _)| kind).collect();
+        assert_eq!(kinds, [*b"IHDR", *b"tEXt", *b"IEND"]);
+        for (kind, data, end) in chunks(&png) {
+            let mut typed = kind.to_vec();
+            typed.extend_from_slice(data);
+            assert_eq!(png[end - 4..end], crc32(&typed).to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(add_text(b"GIF89a", "guides", ""), None);
+        assert_eq!(read_text(b"GIF89a", "guides"), None);
+        let truncated = &minimal_png()[..20];
+        assert_eq!(add_text(truncated, "guides", ""), None);
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::color::Color;
+
+/// Non-premultiplied RGBA pixels, row by row, the layout of `ImageData`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    /// `None` unless `data` holds exactly `width * height` pixels.
+    pub fn from_data(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        let len = (width as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(4)?;
+        (data.len() == len).then_some(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        Color::new(
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        )
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.index(x, y);
+        self.data[i] = color.get_r();
+        self.data[i + 1] = color.get_g();
+        self.data[i + 2] = color.get_b();
+        self.data[i + 3] = color.get_a();
+    }
+
+    /// Smallest rectangle `(x, y, width, height)` containing every non-transparent pixel.
+    pub fn opaque_bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        self.bounds_where(|pixel| pixel[3] != 0)
+    }
+
+    /// Smallest rectangle containing every pixel that differs from the top left one,
+    /// fully transparent pixels being equal whatever their color.
+    pub fn content_bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let corner: [u8; 4] = self.data[..4].try_into().unwrap();
+        self.bounds_where(|pixel| match (corner[3], pixel[3]) {
+            (0, 0) => false,
+            _ => pixel != corner,
+        })
+    }
+
+    fn bounds_where(&self, keep: impl Fn(&[u8]) -> bool) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = self.index(x, y);
+                if !keep(&self.data[i..i + 4]) {
+                    continue;
+                }
+                bounds = Some(match bounds {
+                    None => (x, y, x, y),
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                });
+            }
+        }
+        bounds.map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+    }
+
+    /// Copies `image` over the pixels starting at `(x, y)`, cutting off what doesn't fit.
+    pub fn paste(&mut self, image: &RgbaImage, x: u32, y: u32) {
+        let len = (image.width.min(self.width.saturating_sub(x)) * 4) as usize;
+        if len == 0 {
+            return;
+        }
+        for dy in 0..image.height.min(self.height.saturating_sub(y)) {
+            let from = image.index(0, dy);
+            let to = self.index(x, y + dy);
+            self.data[to..to + len].copy_from_slice(&image.data[from..from + len]);
+        }
+    }
+
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let mut result = Self::new(width, height);
+        let len = (width.min(self.width.saturating_sub(x)) * 4) as usize;
+        if len == 0 {
+            return result;
+        }
+        for dy in 0..height.min(self.height.saturating_sub(y)) {
+            let from = self.index(x, y + dy);
+            let to = result.index(0, dy);
+            result.data[to..to + len].copy_from_slice(&self.data[from..from + len]);
+        }
+        result
+    }
+
+    /// Brush tip where dark and opaque pixels paint and light or transparent ones don't.
+    pub fn to_mask(&self) -> GrayImage {
+        let data = self
+            .data
+            .chunks(4)
+            .map(|p| {
+                let luma =
+                    (p[0] as f64 * 0.2126 + p[1] as f64 * 0.7152 + p[2] as f64 * 0.0722) / 255.0;
+                ((1.0 - luma) * p[3] as f64).round() as u8
+            })
+            .collect();
+        GrayImage::from_data(self.width, self.height, data).unwrap()
+    }
+
+    /// Luminance of every pixel, transparent pixels are black.
+    pub fn to_luminance(&self) -> GrayImage {
+        let data = self
+            .data
+            .chunks(4)
+            .map(|p| {
+                let luma =
+                    (p[0] as f64 * 0.2126 + p[1] as f64 * 0.7152 + p[2] as f64 * 0.0722) / 255.0;
+                (luma * p[3] as f64).round() as u8
+            })
+            .collect();
+        GrayImage::from_data(self.width, self.height, data).unwrap()
+    }
+
+    /// Nearest-neighbour downscale so that neither side exceeds `max_side`.
+    pub fn fit(&self, max_side: u32) -> Self {
+        let longest = self.width.max(self.height);
+        if longest <= max_side {
+            return self.clone();
+        }
+        let width = (self.width * max_side / longest).max(1);
+        let height = (self.height * max_side / longest).max(1);
+        let mut result = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = self.get(x * self.width / width, y * self.height / height);
+                result.set(x, y, color);
+            }
+        }
+        result
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        ((y * self.width + x) * 4) as usize
+    }
+}
+
+/// Single channel image, used for brush tips where a value is the paint coverage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GrayImage {
+    pub width: u32,
+    pub height: u32,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl GrayImage {
+    /// `None` unless `data` holds exactly `width * height` pixels.
+    pub fn from_data(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        let len = (width as usize).checked_mul(height as usize)?;
+        (data.len() == len).then_some(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Black image with the mask in the alpha channel, ready to be drawn and tinted.
+    pub fn to_rgba(&self) -> RgbaImage {
+        let data = self.data.iter().flat_map(|&v| [0, 0, 0, v]).collect();
+        RgbaImage::from_data(self.width, self.height, data).unwrap()
+    }
+
+    /// Nearest-neighbour downscale so that neither side exceeds `max_side`.
+    pub fn fit(&self, max_side: u32) -> Self {
+        let longest = self.width.max(self.height);
+        if longest <= max_side {
+            return self.clone();
+        }
+        let width = (self.width * max_side / longest).max(1);
+        let height = (self.height * max_side / longest).max(1);
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let sx = x * self.width / width;
+                let sy = y * self.height / height;
+                data.push(self.data[(sy * self.width + sx) as usize]);
+            }
+        }
+        Self::from_data(width, height, data).unwrap()
+    }
+}
+
+mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        crate::base64::decode(&text).ok_or_else(|| serde::de::Error::custom("invalid base64"))
+    }
+}
@@ -0,0 +1,52 @@
+use gloo::{
+    render::{request_animation_frame, AnimationFrame},
+    timers::callback::Timeout,
+};
+use yew::{html::Scope, Component};
+
+/// Coalesces the redraws a component asks for, so it draws at most once per
+/// animation frame however many changes come in between.
+///
+/// Throttled schedulers wait a fixed delay instead, for views nobody watches closely.
+#[derive(Default)]
+pub struct Redraw {
+    delay_millis: u32,
+    frame: Option<AnimationFrame>,
+    timeout: Option<Timeout>,
+}
+
+impl Redraw {
+    pub fn throttled(delay_millis: u32) -> Self {
+        Self {
+            delay_millis,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.frame.is_some() || self.timeout.is_some()
+    }
+
+    /// Sends `msg` to the component when it's time to draw, unless a redraw is
+    /// already on the way.
+    pub fn request<C: Component>(&mut self, link: &Scope<C>, msg: C::Message) {
+        if self.is_pending() {
+            return;
+        }
+        let link = link.clone();
+        if self.delay_millis > 0 {
+            self.timeout = Some(Timeout::new(self.delay_millis, move || {
+                link.send_message(msg)
+            }));
+        } else {
+            self.frame = Some(request_animation_frame(move |_| link.send_message(msg)));
+        }
+    }
+
+    /// Marks the requested redraw as happening, cancelling it if it's still to come.
+    /// Later requests schedule a new one.
+    pub fn done(&mut self) {
+        self.frame = None;
+        self.timeout = None;
+    }
+}
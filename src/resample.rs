@@ -0,0 +1,325 @@
+use std::f64::consts::PI;
+
+use crate::raster::RgbaImage;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    Bicubic,
+    Lanczos3,
+    /// Average of the covered area, best for shrinking.
+    Box,
+}
+
+impl Filter {
+    pub const ALL: [Filter; 5] = [
+        Filter::Nearest,
+        Filter::Bilinear,
+        Filter::Bicubic,
+        Filter::Lanczos3,
+        Filter::Box,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Filter::Nearest => "Nearest neighbour",
+            Filter::Bilinear => "Bilinear",
+            Filter::Bicubic => "Bicubic",
+            Filter::Lanczos3 => "Lanczos3",
+            Filter::Box => "Box",
+        }
+    }
+
+    /// Radius of the kernel in source pixels when not shrinking.
+    fn support(self) -> f64 {
+        match self {
+            Filter::Nearest | Filter::Box => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Bicubic => 2.0,
+            Filter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn kernel(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Filter::Nearest | Filter::Box => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Bilinear => (1.0 - x).max(0.0),
+            Filter::Bicubic => {
+                // Keys cubic with a = -0.5
+                const A: f64 = -0.5;
+                if x < 1.0 {
+                    ((A + 2.0) * x - (A + 3.0)) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((A * x - 5.0 * A) * x + 8.0 * A) * x - 4.0 * A
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos3 => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Source pixels contributing to one destination pixel: the first index and the
+/// weights of it and the following pixels, summing to one.
+struct Contribution {
+    start: usize,
+    weights: Vec<f64>,
+}
+
+fn contributions(source: u32, destination: u32, filter: Filter) -> Vec<Contribution> {
+    let scale = source as f64 / destination as f64;
+    // Shrinking stretches the kernel over every covered source pixel
+    let stretch = scale.max(1.0);
+    let support = filter.support() * stretch;
+
+    (0..destination)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * scale;
+            if filter == Filter::Nearest {
+                let start = (center.floor() as usize).min(source as usize - 1);
+                return Contribution {
+                    start,
+                    weights: vec![1.0],
+                };
+            }
+
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(source as usize);
+            let mut weights: Vec<f64> = (start..end)
+                .map(|j| match filter {
+                    // Exact overlap of the source pixel with the destination footprint
+                    Filter::Box => {
+                        let (from, to) = (center - stretch / 2.0, center + stretch / 2.0);
+                        (to.min(j as f64 + 1.0) - from.max(j as f64)).max(0.0)
+                    }
+                    _ => filter.kernel((j as f64 + 0.5 - center) / stretch),
+                })
+                .collect();
+            let sum: f64 = weights.iter().sum();
+            if sum.abs() < 1e-12 {
+                let nearest = (center.floor() as usize).clamp(start, end - 1);
+                weights = (start..end).map(|j| (j == nearest) as u8 as f64).collect();
+            } else {
+                weights.iter_mut().for_each(|w| *w /= sum);
+            }
+            Contribution { start, weights }
+        })
+        .collect()
+}
+
+/// Resamples the image to the new size.
+///
+/// Filtering happens on premultiplied colors so that the color of transparent
+/// pixels doesn't bleed into their neighbours.
+pub fn resample(image: &RgbaImage, width: u32, height: u32, filter: Filter) -> RgbaImage {
+    let (width, height) = (width.max(1), height.max(1));
+    if image.width == 0 || image.height == 0 {
+        return RgbaImage::new(width, height);
+    }
+
+    let premultiplied: Vec<f64> = image
+        .data
+        .chunks(4)
+        .flat_map(|p| {
+            let alpha = p[3] as f64 / 255.0;
+            [
+                p[0] as f64 / 255.0 * alpha,
+                p[1] as f64 / 255.0 * alpha,
+                p[2] as f64 / 255.0 * alpha,
+                alpha,
+            ]
+        })
+        .collect();
+
+    // Rows first, then columns
+    let columns = contributions(image.width, width, filter);
+    let mut horizontal = vec![0.0; (width * image.height * 4) as usize];
+    for y in 0..image.height as usize {
+        let row = &premultiplied[y * image.width as usize * 4..];
+        for (x, contribution) in columns.iter().enumerate() {
+            let out = (y * width as usize + x) * 4;
+            for (k, weight) in contribution.weights.iter().enumerate() {
+                let from = (contribution.start + k) * 4;
+                for c in 0..4 {
+                    horizontal[out + c] += row[from + c] * weight;
+                }
+            }
+        }
+    }
+
+    let rows = contributions(image.height, height, filter);
+    let mut result = RgbaImage::new(width, height);
+    for (y, contribution) in rows.iter().enumerate() {
+        for x in 0..width as usize {
+            let mut pixel = [0.0; 4];
+            for (k, weight) in contribution.weights.iter().enumerate() {
+                let from = ((contribution.start + k) * width as usize + x) * 4;
+                for c in 0..4 {
+                    pixel[c] += horizontal[from + c] * weight;
+                }
+            }
+
+            // Negative lobes may overshoot, premultiplied colors can't exceed alpha
+            let alpha = pixel[3].clamp(0.0, 1.0);
+            let out = (y * width as usize + x) * 4;
+            for (c, value) in pixel[..3].iter().enumerate() {
+                let color = if alpha > 0.0 {
+                    value.clamp(0.0, alpha) / alpha
+                } else {
+                    0.0
+                };
+                result.data[out + c] = (color * 255.0).round() as u8;
+            }
+            result.data[out + 3] = (alpha * 255.0).round() as u8;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn image(width: u32, height: u32, pixels: &[Color]) -> RgbaImage {
+        let mut image = RgbaImage::new(width, height);
+        for (i, &color) in pixels.iter().enumerate() {
+            image.set(i as u32 % width, i as u32 / width, color);
+        }
+        image
+    }
+
+    fn gray(value: u8) -> Color {
+        Color::new(value, value, value, 255)
+    }
+
+    fn pattern() -> RgbaImage {
+        let pixels: Vec<_> = (0..25).map(|i| gray((i * 37 % 256) as u8)).collect();
+        image(5, 5, &pixels)
+    }
+
+    #[test]
+    fn same_size_is_identity() {
+        let source = pattern();
+        for filter in Filter::ALL {
+            assert_eq!(resample(&source, 5, 5, filter), source, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn uniform_color_stays_uniform() {
+        let color = Color::new(10, 200, 30, 255);
+        let source = image(7, 5, &[color; 35]);
+        for filter in Filter::ALL {
+            for (width, height) in [(3, 2), (13, 11), (7, 1)] {
+                let result = resample(&source, width, height, filter);
+                for y in 0..height {
+                    for x in 0..width {
+                        assert_eq!(result.get(x, y), color, "{:?} {}x{}", filter, width, height);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_duplicates_pixels() {
+        let source = image(2, 1, &[gray(0), gray(255)]);
+        let result = resample(&source, 4, 2, Filter::Nearest);
+        let row: Vec<_> = (0..4).map(|x| result.get(x, 1)).collect();
+        assert_eq!(row, [gray(0), gray(0), gray(255), gray(255)]);
+    }
+
+    #[test]
+    fn bilinear_interpolates_between_centers() {
+        let source = image(2, 1, &[gray(0), gray(255)]);
+        let result = resample(&source, 4, 1, Filter::Bilinear);
+        let row: Vec<_> = (0..4).map(|x| result.get(x, 0).get_r()).collect();
+        assert_eq!(row, [0, 64, 191, 255]);
+    }
+
+    #[test]
+    fn box_averages_covered_pixels() {
+        let source = image(
+            4,
+            2,
+            &[
+                gray(0),
+                gray(255),
+                gray(100),
+                gray(100),
+                gray(255),
+                gray(0),
+                gray(100),
+                gray(100),
+            ],
+        );
+        let result = resample(&source, 2, 1, Filter::Box);
+        assert_eq!(result.get(0, 0), gray(128));
+        assert_eq!(result.get(1, 0), gray(100));
+    }
+
+    #[test]
+    fn box_handles_fractional_coverage() {
+        // The middle source pixel is split between both destination pixels
+        let source = image(3, 1, &[gray(0), gray(90), gray(180)]);
+        let result = resample(&source, 2, 1, Filter::Box);
+        assert_eq!(result.get(0, 0).get_r(), 30);
+        assert_eq!(result.get(1, 0).get_r(), 150);
+    }
+
+    #[test]
+    fn transparent_colors_do_not_bleed() {
+        let red = Color::new(255, 0, 0, 255);
+        let clear_green = Color::new(0, 255, 0, 0);
+        let source = image(2, 1, &[red, clear_green]);
+        for filter in [
+            Filter::Bilinear,
+            Filter::Bicubic,
+            Filter::Lanczos3,
+            Filter::Box,
+        ] {
+            let result = resample(&source, 1, 1, filter);
+            let pixel = result.get(0, 0);
+            assert_eq!(
+                (pixel.get_r(), pixel.get_g(), pixel.get_b()),
+                (255, 0, 0),
+                "{:?}",
+                filter
+            );
+            assert_eq!(pixel.get_a(), 128, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn overshoot_is_clamped() {
+        // Bicubic rings past both ends of a hard edge
+        let source = image(2, 1, &[gray(0), gray(255)]);
+        let result = resample(&source, 4, 1, Filter::Bicubic);
+        assert_eq!(result.get(0, 0), gray(0));
+        assert_eq!(result.get(3, 0), gray(255));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{cell::RefCell, collections::HashMap, ops::Deref, rc::Rc};
+
+use gloo::storage::{errors::StorageError, LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use web_sys::HtmlCanvasElement;
+
+use crate::{
+    grain::{Grain, GrainSource},
+    raster::{GrayImage, RgbaImage},
+    virtual_context::canvas_from_image,
+};
+
+const STORAGE_KEY: &str = "paint_wasm.resources";
+
+/// Largest side of a captured brush tip, bigger captures are scaled down.
+pub const MAX_TIP_SIDE: u32 = 256;
+
+/// Largest side of a pattern, bigger ones are scaled down to fit in `localStorage`.
+pub const MAX_PATTERN_SIDE: u32 = 512;
+
+/// Largest side of an imported grain texture.
+pub const MAX_GRAIN_SIDE: u32 = 512;
+
+pub struct RcResources(pub Rc<RefCell<Resources>>);
+
+impl From<Resources> for RcResources {
+    fn from(resources: Resources) -> Self {
+        RcResources(Rc::new(RefCell::new(resources)))
+    }
+}
+
+impl Deref for RcResources {
+    type Target = Rc<RefCell<Resources>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Clone for RcResources {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl PartialEq for RcResources {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BrushTip {
+    pub id: u32,
+    pub name: String,
+    pub mask: GrayImage,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pattern {
+    pub id: u32,
+    pub name: String,
+    pub image: RgbaImage,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GrainTexture {
+    pub id: u32,
+    pub name: String,
+    pub texture: GrayImage,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Stored {
+    tips: Vec<BrushTip>,
+    patterns: Vec<Pattern>,
+    grains: Vec<GrainTexture>,
+    next_id: u32,
+}
+
+/// User brush tips, fill patterns and grain textures, kept in `localStorage`.
+///
+/// Every tip and pattern has a canvas with its pixels for drawing, tips are stored
+/// there as black with the mask in the alpha channel.
+#[derive(Default)]
+pub struct Resources {
+    stored: Stored,
+    canvases: HashMap<u32, HtmlCanvasElement>,
+    /// Coverage canvas of the last used grain, rebuilt when the grain changes.
+    grain_cache: Option<(Grain, HtmlCanvasElement)>,
+}
+
+impl Resources {
+    pub fn load() -> Self {
+        let stored = LocalStorage::get::<Stored>(STORAGE_KEY).unwrap_or_default();
+        let mut canvases = HashMap::new();
+        for tip in stored.tips.iter() {
+            canvases.insert(tip.id, canvas_from_image(&tip.mask.to_rgba()));
+        }
+        for pattern in stored.patterns.iter() {
+            canvases.insert(pattern.id, canvas_from_image(&pattern.image));
+        }
+        Self {
+            stored,
+            canvases,
+            grain_cache: None,
+        }
+    }
+
+    pub fn iter_tips(&self) -> impl Iterator<Item = &BrushTip> + '_ {
+        self.stored.tips.iter()
+    }
+
+    pub fn iter_patterns(&self) -> impl Iterator<Item = &Pattern> + '_ {
+        self.stored.patterns.iter()
+    }
+
+    pub fn iter_grains(&self) -> impl Iterator<Item = &GrainTexture> + '_ {
+        self.stored.grains.iter()
+    }
+
+    /// Adds a tip and returns its id, or the error if it couldn't be saved in which
+    /// case it isn't added.
+    pub fn add_tip(&mut self, name: String, mask: GrayImage) -> Result<u32, StorageError> {
+        let id = self.next_id();
+        let mask = mask.fit(MAX_TIP_SIDE);
+        self.canvases.insert(id, canvas_from_image(&mask.to_rgba()));
+        self.stored.tips.push(BrushTip { id, name, mask });
+        self.save_added(id)
+    }
+
+    /// Adds a pattern like `add_tip`.
+    pub fn add_pattern(&mut self, name: String, image: RgbaImage) -> Result<u32, StorageError> {
+        let id = self.next_id();
+        let image = image.fit(MAX_PATTERN_SIDE);
+        self.canvases.insert(id, canvas_from_image(&image));
+        self.stored.patterns.push(Pattern { id, name, image });
+        self.save_added(id)
+    }
+
+    /// Adds a grain texture like `add_tip`.
+    pub fn add_grain(&mut self, name: String, texture: GrayImage) -> Result<u32, StorageError> {
+        let id = self.next_id();
+        let texture = texture.fit(MAX_GRAIN_SIDE);
+        self.stored.grains.push(GrainTexture { id, name, texture });
+        self.save_added(id)
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.forget(id);
+        if let Err(e) = self.save() {
+            log::error!("Failed to save brush resources: {}", e);
+        }
+    }
+
+    fn forget(&mut self, id: u32) {
+        self.stored.tips.retain(|tip| tip.id != id);
+        self.stored.patterns.retain(|pattern| pattern.id != id);
+        self.stored.grains.retain(|grain| grain.id != id);
+        if matches!(self.grain_cache, Some((grain, _)) if grain.source == GrainSource::Imported(id))
+        {
+            self.grain_cache = None;
+        }
+        self.canvases.remove(&id);
+    }
+
+    pub fn get_canvas(&self, id: u32) -> Option<&'_ HtmlCanvasElement> {
+        self.canvases.get(&id)
+    }
+
+    /// Canvas with the grain coverage in the alpha channel, `None` if the grain was removed.
+    pub fn grain_canvas(&mut self, grain: Grain) -> Option<HtmlCanvasElement> {
+        if let Some((cached, canvas)) = &self.grain_cache {
+            if *cached == grain {
+                return Some(canvas.clone());
+            }
+        }
+        let texture = match grain.source {
+            GrainSource::Paper(paper) => paper.generate(),
+            GrainSource::Imported(id) => self
+                .stored
+                .grains
+                .iter()
+                .find(|g| g.id == id)?
+                .texture
+                .clone(),
+        };
+        let canvas = canvas_from_image(&grain.coverage(&texture).to_rgba());
+        self.grain_cache = Some((grain, canvas.clone()));
+        Some(canvas)
+    }
+
+    fn save(&self) -> Result<(), StorageError> {
+        LocalStorage::set(STORAGE_KEY, &self.stored)
+    }
+
+    /// Saves with the resource `id` just added, dropping it again if storage is full
+    /// so it doesn't vanish on the next reload unnoticed.
+    fn save_added(&mut self, id: u32) -> Result<u32, StorageError> {
+        match self.save() {
+            Ok(()) => Ok(id),
+            Err(e) => {
+                self.forget(id);
+                Err(e)
+            }
+        }
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.stored.next_id;
+        self.stored.next_id += 1;
+        id
+    }
+}
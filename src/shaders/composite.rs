@@ -0,0 +1,141 @@
+use web_sys::{
+    WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlTexture, WebGlUniformLocation,
+};
+
+use crate::vector::{Rectangle, Transform};
+
+use super::{init_shader_program, make_f32_buffer, uniform_texture};
+
+/// Draws premultiplied textures over what is already there, each into a rectangle
+/// mapped by a transform to canvas pixels.
+pub struct CompositeShader {
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    buffer_length: i32,
+
+    width: i32,
+    height: i32,
+
+    vertex_location: u32,
+    image_location: WebGlUniformLocation,
+    rect_location: WebGlUniformLocation,
+    source_location: WebGlUniformLocation,
+    transform_location: WebGlUniformLocation,
+    size_location: WebGlUniformLocation,
+}
+
+const VS_SOURCE: &str = include_str!("src/composite.vert");
+const FS_SOURCE: &str = include_str!("src/composite.frag");
+
+impl CompositeShader {
+    pub fn new(gl: &Gl, width: i32, height: i32) -> Self {
+        let program = init_shader_program(gl, VS_SOURCE, FS_SOURCE);
+
+        let buffer = make_f32_buffer(
+            gl,
+            &[
+                0.0, 0.0, 1.0, 0.0, 0.0, 1.0, //
+                0.0, 1.0, 1.0, 0.0, 1.0, 1.0,
+            ],
+        );
+        let buffer_length = 6;
+        let vertex_location = gl.get_attrib_location(&program, "vertexPosition") as u32;
+        let image_location = gl.get_uniform_location(&program, "image").unwrap();
+        let rect_location = gl.get_uniform_location(&program, "rect").unwrap();
+        let source_location = gl.get_uniform_location(&program, "source").unwrap();
+        let transform_location = gl.get_uniform_location(&program, "transform").unwrap();
+        let size_location = gl.get_uniform_location(&program, "size").unwrap();
+        Self {
+            program,
+            buffer,
+            buffer_length,
+            width,
+            height,
+            vertex_location,
+            image_location,
+            rect_location,
+            source_location,
+            transform_location,
+            size_location,
+        }
+    }
+
+    pub fn set_size(&mut self, w: i32, h: i32) {
+        self.width = w;
+        self.height = h;
+    }
+
+    /// Readies the shader for `draw` calls through `transform`, sampling the textures
+    /// smoothly or by the nearest pixel.
+    pub fn begin(&self, gl: &Gl, transform: &Transform) {
+        gl.viewport(0, 0, self.width, self.height);
+
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        gl.vertex_attrib_pointer_with_i32(self.vertex_location, 2, Gl::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(self.vertex_location);
+
+        gl.use_program(Some(&self.program));
+
+        let t = transform;
+        gl.uniform_matrix3fv_with_f32_array(
+            Some(&self.transform_location),
+            false,
+            &[
+                t.a as f32, t.b as f32, 0.0, //
+                t.c as f32, t.d as f32, 0.0, //
+                t.e as f32, t.f as f32, 1.0,
+            ],
+        );
+        gl.uniform2f(
+            Some(&self.size_location),
+            self.width as f32,
+            self.height as f32,
+        );
+
+        gl.enable(Gl::BLEND);
+        gl.blend_func(Gl::ONE, Gl::ONE_MINUS_SRC_ALPHA);
+    }
+
+    /// Draws the `source` part of `image`, in texture coordinates, into `rect`.
+    pub fn draw(
+        &self,
+        gl: &Gl,
+        image: &WebGlTexture,
+        rect: Rectangle,
+        source: Rectangle,
+        smooth: bool,
+    ) {
+        gl.bind_texture(Gl::TEXTURE_2D, Some(image));
+        let (min, mag) = if smooth {
+            (Gl::LINEAR_MIPMAP_LINEAR, Gl::LINEAR)
+        } else {
+            (Gl::NEAREST, Gl::NEAREST)
+        };
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, min as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, mag as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32);
+
+        uniform_texture(gl, &self.image_location, image);
+        gl.uniform4f(
+            Some(&self.rect_location),
+            rect.coord.x as f32,
+            rect.coord.y as f32,
+            rect.size.x as f32,
+            rect.size.y as f32,
+        );
+        gl.uniform4f(
+            Some(&self.source_location),
+            source.coord.x as f32,
+            source.coord.y as f32,
+            source.size.x as f32,
+            source.size.y as f32,
+        );
+
+        gl.draw_arrays(Gl::TRIANGLES, 0, self.buffer_length);
+    }
+
+    pub fn end(&self, gl: &Gl) {
+        gl.disable(Gl::BLEND);
+    }
+}
@@ -0,0 +1,110 @@
+use web_sys::{
+    WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlTexture, WebGlUniformLocation,
+};
+
+use crate::{
+    dodge_burn::{DodgeBurn, ToneMode},
+    vector::Vector2,
+};
+
+use super::{init_shader_program, make_f32_buffer, uniform_texture, VS_SOURCE};
+
+pub struct DodgeBurnShader {
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    buffer_length: i32,
+
+    width: i32,
+    height: i32,
+
+    vertex_location: u32,
+    image_location: WebGlUniformLocation,
+    size_location: WebGlUniformLocation,
+    center_location: WebGlUniformLocation,
+    radius_location: WebGlUniformLocation,
+    mode_location: WebGlUniformLocation,
+    range_location: WebGlUniformLocation,
+    exposure_location: WebGlUniformLocation,
+}
+
+const FS_SOURCE: &str = include_str!("src/dodge_burn.frag");
+
+impl DodgeBurnShader {
+    pub fn new(gl: &Gl, width: i32, height: i32) -> Self {
+        let program = init_shader_program(gl, VS_SOURCE, FS_SOURCE);
+
+        let buffer = make_f32_buffer(
+            gl,
+            &[
+                -1.0, -1.0, 1.0, -1.0, -1.0, 1.0, //
+                -1.0, 1.0, 1.0, -1.0, 1.0, 1.0,
+            ],
+        );
+        let buffer_length = 6;
+        let vertex_location = gl.get_attrib_location(&program, "vertexPosition") as u32;
+        let image_location = gl.get_uniform_location(&program, "image").unwrap();
+        let size_location = gl.get_uniform_location(&program, "size").unwrap();
+        let center_location = gl.get_uniform_location(&program, "center").unwrap();
+        let radius_location = gl.get_uniform_location(&program, "radius").unwrap();
+        let mode_location = gl.get_uniform_location(&program, "mode").unwrap();
+        let range_location = gl.get_uniform_location(&program, "range").unwrap();
+        let exposure_location = gl.get_uniform_location(&program, "exposure").unwrap();
+        Self {
+            program,
+            buffer,
+            buffer_length,
+            width,
+            height,
+            vertex_location,
+            image_location,
+            size_location,
+            center_location,
+            radius_location,
+            mode_location,
+            range_location,
+            exposure_location,
+        }
+    }
+
+    pub fn set_size(&mut self, w: i32, h: i32) {
+        self.width = w;
+        self.height = h;
+    }
+
+    pub fn draw(
+        &self,
+        gl: &Gl,
+        center: Vector2,
+        radius: f64,
+        mode: ToneMode,
+        settings: DodgeBurn,
+        image: &WebGlTexture,
+    ) {
+        gl.viewport(0, 0, self.width, self.height);
+
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.buffer));
+        gl.vertex_attrib_pointer_with_i32(self.vertex_location, 2, Gl::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(self.vertex_location);
+
+        gl.use_program(Some(&self.program));
+
+        uniform_texture(gl, &self.image_location, image);
+
+        gl.uniform2f(
+            Some(&self.size_location),
+            self.width as f32,
+            self.height as f32,
+        );
+        gl.uniform2f(
+            Some(&self.center_location),
+            center.x as f32,
+            center.y as f32,
+        );
+        gl.uniform1f(Some(&self.radius_location), radius as f32);
+        gl.uniform1i(Some(&self.mode_location), mode.to_shader_index());
+        gl.uniform1i(Some(&self.range_location), settings.range.to_shader_index());
+        gl.uniform1f(Some(&self.exposure_location), settings.exposure as f32);
+
+        gl.draw_arrays(Gl::TRIANGLES, 0, self.buffer_length);
+    }
+}
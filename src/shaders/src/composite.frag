@@ -0,0 +1,12 @@
+#version 300 es
+precision mediump float;
+
+uniform sampler2D image;
+
+in vec2 texCoord;
+
+out vec4 color;
+
+void main() {
+    color = texture(image, texCoord);
+}
@@ -0,0 +1,17 @@
+#version 300 es
+
+in vec2 vertexPosition;
+
+uniform vec4 rect;
+// Part of the texture drawn into `rect`, in texture coordinates
+uniform vec4 source;
+uniform mat3 transform;
+uniform vec2 size;
+
+out vec2 texCoord;
+
+void main() {
+    vec2 point = (transform * vec3(rect.xy + vertexPosition * rect.zw, 1.0)).xy;
+    gl_Position = vec4(point.x / size.x * 2.0 - 1.0, 1.0 - point.y / size.y * 2.0, 0.0, 1.0);
+    texCoord = source.xy + vertexPosition * source.zw;
+}
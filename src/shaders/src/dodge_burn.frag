@@ -0,0 +1,61 @@
+#version 300 es
+precision mediump float;
+
+uniform sampler2D image;
+uniform vec2 size;
+uniform vec2 center;
+uniform float radius;
+uniform int mode;
+uniform int range;
+uniform float exposure;
+
+in vec2 fragCoord;
+
+out vec4 color;
+
+// Mirrors `DodgeBurn::tone_channel` in dodge_burn.rs
+// At exposure 0.6 a channel of 0.5 becomes:
+//   dodge: shadows 0.6, midtones 0.64842, highlights 0.6
+//   burn: shadows 0.375, midtones 0.32988, highlights 0.4
+vec3 tone(vec3 v) {
+    float e = clamp(exposure, 0.0, 1.0);
+    float factor = e / 3.0;
+
+    vec3 result = v;
+
+    if (mode == 0) {
+        if (range == 2) result = v * (1.0 + factor);
+        else if (range == 1) result = pow(v, vec3(1.0 / (1.0 + e)));
+        else result = factor + v - factor * v;
+    } else if (mode == 1) {
+        if (range == 2) result = v * (1.0 - factor);
+        else if (range == 1) result = pow(v, vec3(1.0 + e));
+        else result = (v - factor) / (1.0 - factor);
+    }
+
+    return clamp(result, 0.0, 1.0);
+}
+
+// Mirrors `DodgeBurn::sponge` in dodge_burn.rs
+// At exposure 0.6 (0.8, 0.4, 0.2) becomes:
+//   saturate: (0.99764, 0.35764, 0.03764)
+//   desaturate: (0.60236, 0.44236, 0.36236)
+vec3 sponge(vec3 v) {
+    float e = clamp(exposure, 0.0, 1.0);
+    float luma = dot(v, vec3(0.2126, 0.7152, 0.0722));
+    float factor = mode == 2 ? 1.0 + e : 1.0 - e;
+    return clamp(vec3(luma) + (v - vec3(luma)) * factor, 0.0, 1.0);
+}
+
+void main() {
+    vec2 pixel = vec2(fragCoord.x + 1.0, 1.0 - fragCoord.y) * 0.5 * size;
+    vec4 source = texelFetch(image, ivec2(pixel), 0);
+
+    float dist = distance(pixel, center);
+    float strength = clamp(radius - dist, 0.0, 1.0);
+
+    vec3 adjusted = mode < 2 ? tone(source.rgb) : sponge(source.rgb);
+    vec3 rgb = mix(source.rgb, adjusted, strength);
+
+    color = vec4(rgb * source.a, source.a);
+}
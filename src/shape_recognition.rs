@@ -0,0 +1,667 @@
+use std::f64::consts::TAU;
+
+use crate::vector::{Ellipse, Vector2};
+
+/// Points a stroke is resampled to before it's analyzed.
+const SAMPLES: usize = 64;
+
+/// Strokes shorter than this are never recognized.
+const MIN_LENGTH: f64 = 20.0;
+
+/// A stroke is closed when its ends are closer than this part of its length.
+const CLOSE_RATIO: f64 = 0.15;
+
+/// Largest distance from the chord, relative to its length, of a line.
+const LINE_TOLERANCE: f64 = 0.05;
+
+/// Largest mean distance from the fitted circle, relative to its radius, of an arc.
+const ARC_TOLERANCE: f64 = 0.06;
+
+/// Simplification distance of closed strokes, relative to their size.
+const POLYGON_EPSILON: f64 = 0.15;
+
+/// Turns smaller than this are not corners.
+const MIN_CORNER_ANGLE: f64 = 0.45;
+
+/// Largest mean distance of a closed stroke from its shape, relative to its size.
+const CLOSED_TOLERANCE: f64 = 0.1;
+
+/// Segments of curved outlines.
+const CURVE_SEGMENTS: usize = 96;
+
+/// A clean geometric shape, every shape is defined by its handles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Line([Vector2; 2]),
+    /// Circular arc from the first point through the second to the third.
+    Arc([Vector2; 3]),
+    /// Corners in order around the rectangle.
+    Rectangle([Vector2; 4]),
+    Ellipse(Ellipse),
+    Triangle([Vector2; 3]),
+}
+
+impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Line(_) => "Line",
+            Shape::Arc(_) => "Arc",
+            Shape::Rectangle(_) => "Rectangle",
+            Shape::Ellipse(_) => "Ellipse",
+            Shape::Triangle(_) => "Triangle",
+        }
+    }
+
+    /// Points to stroke through to draw the shape.
+    pub fn outline(&self) -> Vec<Vector2> {
+        match *self {
+            Shape::Line(points) => points.to_vec(),
+            Shape::Arc([start, through, end]) => arc_polyline(start, through, end),
+            Shape::Rectangle(corners) => closed(&corners),
+            Shape::Ellipse(ellipse) => ellipse.polyline(CURVE_SEGMENTS),
+            Shape::Triangle(corners) => closed(&corners),
+        }
+    }
+
+    /// Points that can be dragged to edit the shape, see `move_handle`.
+    pub fn handles(&self) -> Vec<Vector2> {
+        match *self {
+            Shape::Line(points) => points.to_vec(),
+            Shape::Arc(points) | Shape::Triangle(points) => points.to_vec(),
+            Shape::Rectangle(corners) => corners.to_vec(),
+            Shape::Ellipse(ellipse) => vec![
+                ellipse.center,
+                ellipse.from_unit_circle(Vector2::new(1.0, 0.0)),
+                ellipse.from_unit_circle(Vector2::new(0.0, 1.0)),
+            ],
+        }
+    }
+
+    /// Index of the handle closest to `point`.
+    pub fn closest_handle(&self, point: Vector2) -> usize {
+        self.handles()
+            .iter()
+            .map(|&handle| (handle - point).len())
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+            .unwrap_or_default()
+    }
+
+    /// Drags a handle keeping the kind of the shape: rectangles stay rectangles
+    /// with the opposite corner in place, ellipses move by the center and are
+    /// resized and rotated by the ends of their axes.
+    pub fn move_handle(&mut self, handle: usize, to: Vector2) {
+        match self {
+            Shape::Line(points) => points[handle] = to,
+            Shape::Arc(points) | Shape::Triangle(points) => points[handle] = to,
+            Shape::Rectangle(corners) => {
+                let opposite = corners[(handle + 2) % 4];
+                let u = corners[(handle + 1) % 4] - opposite;
+                let v = corners[(handle + 3) % 4] - opposite;
+                if u.len() == 0.0 || v.len() == 0.0 {
+                    return;
+                }
+                let (u, v) = (u.norm(), v.norm());
+                let (du, dv) = ((to - opposite).dot(u), (to - opposite).dot(v));
+                corners[(handle + 1) % 4] = opposite + u * du;
+                corners[(handle + 3) % 4] = opposite + v * dv;
+                corners[handle] = opposite + u * du + v * dv;
+            }
+            Shape::Ellipse(ellipse) => match handle {
+                0 => ellipse.center = to,
+                1 => {
+                    let major = to - ellipse.center;
+                    ellipse.radii.x = major.len();
+                    ellipse.angle = major.y.atan2(major.x);
+                }
+                _ => {
+                    let minor = Vector2::new(0.0, 1.0).rotate(ellipse.angle);
+                    ellipse.radii.y = (to - ellipse.center).dot(minor).abs();
+                }
+            },
+        }
+    }
+}
+
+/// Recognizes the shape a freehand stroke approximates.
+pub fn recognize(points: &[Vector2]) -> Option<Shape> {
+    let length = path_length(points);
+    if length < MIN_LENGTH {
+        return None;
+    }
+    let samples = resample(points, SAMPLES);
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+
+    if (last - first).len() > CLOSE_RATIO * length {
+        recognize_open(&samples)
+    } else {
+        recognize_closed(&samples[..samples.len() - 1], length)
+    }
+}
+
+fn recognize_open(samples: &[Vector2]) -> Option<Shape> {
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+    let chord = (last - first).len();
+    let deviation = samples
+        .iter()
+        .map(|&p| segment_distance(p, first, last))
+        .fold(0.0, f64::max);
+    if deviation <= LINE_TOLERANCE * chord {
+        return Some(Shape::Line([first, last]));
+    }
+
+    let (center, radius) = fit_circle(samples)?;
+    let error = samples
+        .iter()
+        .map(|&p| ((p - center).len() - radius).abs())
+        .sum::<f64>()
+        / samples.len() as f64;
+    if error > ARC_TOLERANCE * radius {
+        return None;
+    }
+    let onto_circle = |p: Vector2| center + (p - center).norm() * radius;
+    Some(Shape::Arc([
+        onto_circle(first),
+        onto_circle(samples[samples.len() / 2]),
+        onto_circle(last),
+    ]))
+}
+
+fn recognize_closed(samples: &[Vector2], length: f64) -> Option<Shape> {
+    // Radius of a circle with the same perimeter
+    let size = length / TAU;
+
+    let ellipse = fit_ellipse(samples);
+    let ellipse_error = samples
+        .iter()
+        .map(|&p| (ellipse.to_unit_circle(p).len() - 1.0).abs())
+        .sum::<f64>()
+        / samples.len() as f64
+        * (ellipse.radii.x * ellipse.radii.y).sqrt()
+        / size;
+
+    let corners = corners(samples, POLYGON_EPSILON * size);
+    let polygon = match corners.len() {
+        3 => Some(Shape::Triangle([corners[0], corners[1], corners[2]])),
+        4 => Some(fit_rectangle(samples)),
+        _ => None,
+    };
+    let polygon_error = polygon.map(|shape| match shape {
+        Shape::Triangle(corners) => polygon_distance(samples, &corners),
+        Shape::Rectangle(corners) => polygon_distance(samples, &corners),
+        _ => unreachable!(),
+    } / size);
+
+    match polygon_error {
+        Some(error) if error <= ellipse_error && error <= CLOSED_TOLERANCE => polygon,
+        _ if ellipse_error <= CLOSED_TOLERANCE => Some(Shape::Ellipse(ellipse)),
+        _ => None,
+    }
+}
+
+fn path_length(points: &[Vector2]) -> f64 {
+    points.windows(2).map(|w| (w[1] - w[0]).len()).sum()
+}
+
+/// `count` points evenly spaced along the path, including both ends.
+fn resample(points: &[Vector2], count: usize) -> Vec<Vector2> {
+    let step = path_length(points) / (count - 1) as f64;
+    let mut result = vec![points[0]];
+    let mut distance_to_next = step;
+    for w in points.windows(2) {
+        let (mut from, to) = (w[0], w[1]);
+        let mut len = (to - from).len();
+        while len >= distance_to_next && result.len() < count - 1 {
+            from = from + (to - from) * (distance_to_next / len);
+            result.push(from);
+            len -= distance_to_next;
+            distance_to_next = step;
+        }
+        distance_to_next -= len;
+    }
+    result.push(points[points.len() - 1]);
+    result
+}
+
+fn centroid(points: &[Vector2]) -> Vector2 {
+    let sum = points
+        .iter()
+        .fold(Vector2::new(0.0, 0.0), |sum, &p| sum + p);
+    sum * (1.0 / points.len() as f64)
+}
+
+fn segment_distance(point: Vector2, a: Vector2, b: Vector2) -> f64 {
+    let ab = b - a;
+    let len2 = ab.dot(ab);
+    if len2 == 0.0 {
+        return (point - a).len();
+    }
+    let t = ((point - a).dot(ab) / len2).clamp(0.0, 1.0);
+    (point - (a + ab * t)).len()
+}
+
+/// Mean distance from the points to the closed polygon.
+fn polygon_distance(points: &[Vector2], corners: &[Vector2]) -> f64 {
+    let edges = closed(corners);
+    points
+        .iter()
+        .map(|&p| {
+            edges
+                .windows(2)
+                .map(|e| segment_distance(p, e[0], e[1]))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .sum::<f64>()
+        / points.len() as f64
+}
+
+fn closed(corners: &[Vector2]) -> Vec<Vector2> {
+    let mut points = corners.to_vec();
+    points.push(corners[0]);
+    points
+}
+
+/// Least squares circle through the points, `None` when they are collinear.
+fn fit_circle(points: &[Vector2]) -> Option<(Vector2, f64)> {
+    // Kåsa fit: x² + y² + Dx + Ey + F = 0 around the centroid for stability
+    let origin = centroid(points);
+    let (mut sxx, mut sxy, mut syy, mut sxz, mut syz) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    let (mut sx, mut sy, mut sz) = (0.0, 0.0, 0.0);
+    for &p in points {
+        let Vector2 { x, y } = p - origin;
+        let z = x * x + y * y;
+        sxx += x * x;
+        sxy += x * y;
+        syy += y * y;
+        sxz += x * z;
+        syz += y * z;
+        sx += x;
+        sy += y;
+        sz += z;
+    }
+    let n = points.len() as f64;
+    let (d, e, f) = solve3(
+        [[sxx, sxy, sx], [sxy, syy, sy], [sx, sy, n]],
+        [-sxz, -syz, -sz],
+    )?;
+    let center = Vector2::new(-d / 2.0, -e / 2.0);
+    let radius2 = center.dot(center) - f;
+    if radius2 <= 0.0 {
+        return None;
+    }
+    Some((origin + center, radius2.sqrt()))
+}
+
+/// Cramer's rule, `None` for a singular matrix.
+fn solve3(m: [[f64; 3]; 3], b: [f64; 3]) -> Option<(f64, f64, f64)> {
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let d = det(m);
+    if d.abs() < 1e-9 {
+        return None;
+    }
+    let with_column = |c: usize| {
+        let mut m = m;
+        for (row, value) in m.iter_mut().zip(b) {
+            row[c] = value;
+        }
+        det(m) / d
+    };
+    Some((with_column(0), with_column(1), with_column(2)))
+}
+
+/// Ellipse along the principal axes of the points with least squares radii.
+fn fit_ellipse(points: &[Vector2]) -> Ellipse {
+    let center = centroid(points);
+    let (mut cxx, mut cxy, mut cyy) = (0.0, 0.0, 0.0);
+    for &p in points {
+        let d = p - center;
+        cxx += d.x * d.x;
+        cxy += d.x * d.y;
+        cyy += d.y * d.y;
+    }
+    let angle = 0.5 * (2.0 * cxy).atan2(cxx - cyy);
+
+    // Fit a x² + b y² = 1 in the frame of the axes
+    let (mut sxx, mut sxy, mut syy, mut sx, mut sy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &p in points {
+        let local = (p - center).rotate(-angle);
+        let (x2, y2) = (local.x * local.x, local.y * local.y);
+        sxx += x2 * x2;
+        sxy += x2 * y2;
+        syy += y2 * y2;
+        sx += x2;
+        sy += y2;
+    }
+    let det = sxx * syy - sxy * sxy;
+    let (a, b) = ((sx * syy - sy * sxy) / det, (sy * sxx - sx * sxy) / det);
+    let radius = |k: f64| if k > 0.0 { 1.0 / k.sqrt() } else { 0.0 };
+    Ellipse::new(center, Vector2::new(radius(a), radius(b)), angle)
+}
+
+/// Corners of a closed stroke after Douglas-Peucker simplification.
+fn corners(points: &[Vector2], epsilon: f64) -> Vec<Vector2> {
+    let center = centroid(points);
+    let farthest_from = |from: Vector2| {
+        (0..points.len())
+            .max_by(|&a, &b| {
+                (points[a] - from)
+                    .len()
+                    .total_cmp(&(points[b] - from).len())
+            })
+            .unwrap()
+    };
+    let a = farthest_from(center);
+    let b = farthest_from(points[a]);
+    let (a, b) = (a.min(b), a.max(b));
+
+    let mut first = points[a..=b].to_vec();
+    let mut second = points[b..].to_vec();
+    second.extend_from_slice(&points[..=a]);
+    first = simplify(&first, epsilon);
+    second = simplify(&second, epsilon);
+
+    let mut corners = first;
+    corners.pop();
+    corners.extend_from_slice(&second[..second.len() - 1]);
+
+    // Drop vertices where the outline barely turns
+    loop {
+        let n = corners.len();
+        if n <= 3 {
+            return corners;
+        }
+        let flattest = (0..n)
+            .map(|i| {
+                let before = corners[i] - corners[(i + n - 1) % n];
+                let after = corners[(i + 1) % n] - corners[i];
+                let turn = (before.x * after.y - before.y * after.x)
+                    .atan2(before.dot(after))
+                    .abs();
+                (i, turn)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        if flattest.1 >= MIN_CORNER_ANGLE {
+            return corners;
+        }
+        corners.remove(flattest.0);
+    }
+}
+
+fn simplify(points: &[Vector2], epsilon: f64) -> Vec<Vector2> {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let farthest = (1..points.len().saturating_sub(1))
+        .map(|i| (i, segment_distance(points[i], first, last)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+    match farthest {
+        Some((i, distance)) if distance > epsilon => {
+            let mut result = simplify(&points[..=i], epsilon);
+            result.pop();
+            result.extend(simplify(&points[i..], epsilon));
+            result
+        }
+        _ => vec![first, last],
+    }
+}
+
+/// Bounding rectangle of the points aligned with their principal axes.
+fn fit_rectangle(points: &[Vector2]) -> Shape {
+    let ellipse = fit_ellipse(points);
+    let u = Vector2::new(1.0, 0.0).rotate(ellipse.angle);
+    let v = Vector2::new(0.0, 1.0).rotate(ellipse.angle);
+    let (mut u0, mut u1, mut v0, mut v1) = (
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+    );
+    for &p in points {
+        let (pu, pv) = (p.dot(u), p.dot(v));
+        u0 = u0.min(pu);
+        u1 = u1.max(pu);
+        v0 = v0.min(pv);
+        v1 = v1.max(pv);
+    }
+    let at = |pu: f64, pv: f64| u * pu + v * pv;
+    Shape::Rectangle([at(u0, v0), at(u1, v0), at(u1, v1), at(u0, v1)])
+}
+
+/// Points along the circular arc through three points, a straight line if they are collinear.
+fn arc_polyline(start: Vector2, through: Vector2, end: Vector2) -> Vec<Vector2> {
+    let d = 2.0
+        * (start.x * (through.y - end.y)
+            + through.x * (end.y - start.y)
+            + end.x * (start.y - through.y));
+    if d.abs() < 1e-9 {
+        return vec![start, end];
+    }
+    let sq = |p: Vector2| p.dot(p);
+    let center = Vector2::new(
+        (sq(start) * (through.y - end.y)
+            + sq(through) * (end.y - start.y)
+            + sq(end) * (start.y - through.y))
+            / d,
+        (sq(start) * (end.x - through.x)
+            + sq(through) * (start.x - end.x)
+            + sq(end) * (through.x - start.x))
+            / d,
+    );
+    let radius = (start - center).len();
+    let angle_of = |p: Vector2| (p.y - center.y).atan2(p.x - center.x);
+    let a0 = angle_of(start);
+    let to_through = (angle_of(through) - a0).rem_euclid(TAU);
+    let to_end = (angle_of(end) - a0).rem_euclid(TAU);
+    let sweep = if to_through <= to_end {
+        to_end
+    } else {
+        to_end - TAU
+    };
+
+    let segments = ((sweep.abs() / TAU * CURVE_SEGMENTS as f64).ceil() as usize).max(1);
+    (0..=segments)
+        .map(|i| {
+            let a = a0 + sweep * i as f64 / segments as f64;
+            center + Vector2::new(a.cos(), a.sin()) * radius
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline(corners: &[Vector2], per_edge: usize) -> Vec<Vector2> {
+        let mut points = vec![];
+        for w in corners.windows(2) {
+            for i in 0..per_edge {
+                points.push(w[0] + (w[1] - w[0]) * (i as f64 / per_edge as f64));
+            }
+        }
+        points.push(corners[corners.len() - 1]);
+        points
+    }
+
+    /// Deterministic wobble imitating an unsteady hand.
+    fn wobble(points: Vec<Vector2>, amount: f64) -> Vec<Vector2> {
+        points
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let t = i as f64;
+                p + Vector2::new((t * 1.7).sin(), (t * 2.3).cos()) * amount
+            })
+            .collect()
+    }
+
+    fn close(a: Vector2, b: Vector2, tolerance: f64) -> bool {
+        (a - b).len() <= tolerance
+    }
+
+    #[test]
+    fn recognizes_line() {
+        let points = wobble(
+            polyline(&[Vector2::new(10.0, 10.0), Vector2::new(210.0, 110.0)], 50),
+            1.0,
+        );
+        match recognize(&points) {
+            Some(Shape::Line([a, b])) => {
+                assert!(close(a, Vector2::new(10.0, 10.0), 3.0));
+                assert!(close(b, Vector2::new(210.0, 110.0), 3.0));
+            }
+            other => panic!("expected a line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recognizes_arc() {
+        let center = Vector2::new(100.0, 100.0);
+        let points: Vec<_> = (0..=60)
+            .map(|i| {
+                let a = std::f64::consts::PI * i as f64 / 60.0;
+                center + Vector2::new(a.cos(), a.sin()) * 80.0
+            })
+            .collect();
+        match recognize(&wobble(points, 1.0)) {
+            Some(Shape::Arc([start, through, end])) => {
+                assert!(close(start, Vector2::new(180.0, 100.0), 4.0));
+                assert!(close(through, Vector2::new(100.0, 180.0), 4.0));
+                assert!(close(end, Vector2::new(20.0, 100.0), 4.0));
+            }
+            other => panic!("expected an arc, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recognizes_ellipse() {
+        let expected = Ellipse::new(Vector2::new(200.0, 150.0), Vector2::new(120.0, 60.0), 0.3);
+        let points = wobble(expected.polyline(80), 1.5);
+        match recognize(&points) {
+            Some(Shape::Ellipse(ellipse)) => {
+                assert!(close(ellipse.center, expected.center, 3.0));
+                assert!((ellipse.radii.x - 120.0).abs() < 8.0);
+                assert!((ellipse.radii.y - 60.0).abs() < 8.0);
+                assert!((ellipse.angle.rem_euclid(std::f64::consts::PI) - 0.3).abs() < 0.05);
+            }
+            other => panic!("expected an ellipse, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recognizes_circle_as_ellipse() {
+        let circle = Ellipse::new(Vector2::new(0.0, 0.0), Vector2::new(50.0, 50.0), 0.0);
+        assert!(matches!(
+            recognize(&wobble(circle.polyline(60), 1.0)),
+            Some(Shape::Ellipse(_))
+        ));
+    }
+
+    #[test]
+    fn recognizes_rectangle() {
+        let corners = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(200.0, 0.0),
+            Vector2::new(200.0, 100.0),
+            Vector2::new(0.0, 100.0),
+            Vector2::new(0.0, 0.0),
+        ];
+        match recognize(&wobble(polyline(&corners, 30), 1.5)) {
+            Some(Shape::Rectangle(found)) => {
+                for corner in &corners[..4] {
+                    assert!(found.iter().any(|&c| close(c, *corner, 5.0)));
+                }
+            }
+            other => panic!("expected a rectangle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recognizes_triangle() {
+        let corners = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(200.0, 20.0),
+            Vector2::new(80.0, 160.0),
+            Vector2::new(0.0, 0.0),
+        ];
+        match recognize(&wobble(polyline(&corners, 40), 1.0)) {
+            Some(Shape::Triangle(found)) => {
+                for corner in &corners[..3] {
+                    assert!(found.iter().any(|&c| close(c, *corner, 6.0)));
+                }
+            }
+            other => panic!("expected a triangle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_scribble() {
+        let points: Vec<_> = (0..100)
+            .map(|i| {
+                let t = i as f64 / 10.0;
+                Vector2::new(t * 20.0, (t * 3.0).sin() * 40.0 + (t * 7.0).cos() * 25.0)
+            })
+            .collect();
+        assert_eq!(recognize(&points), None);
+    }
+
+    #[test]
+    fn rejects_short_strokes() {
+        let points = [Vector2::new(0.0, 0.0), Vector2::new(5.0, 5.0)];
+        assert_eq!(recognize(&points), None);
+    }
+
+    #[test]
+    fn moving_rectangle_corner_keeps_it_rectangular() {
+        let mut shape = Shape::Rectangle([
+            Vector2::new(0.0, 0.0),
+            Vector2::new(100.0, 0.0),
+            Vector2::new(100.0, 50.0),
+            Vector2::new(0.0, 50.0),
+        ]);
+        shape.move_handle(2, Vector2::new(150.0, 80.0));
+        assert_eq!(
+            shape,
+            Shape::Rectangle([
+                Vector2::new(0.0, 0.0),
+                Vector2::new(150.0, 0.0),
+                Vector2::new(150.0, 80.0),
+                Vector2::new(0.0, 80.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn moving_ellipse_axis_resizes_and_rotates() {
+        let mut shape = Shape::Ellipse(Ellipse::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 5.0),
+            0.0,
+        ));
+        shape.move_handle(1, Vector2::new(0.0, 20.0));
+        match shape {
+            Shape::Ellipse(ellipse) => {
+                assert!((ellipse.radii.x - 20.0).abs() < 1e-9);
+                assert!((ellipse.angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn arc_outline_passes_through_its_handles() {
+        let (start, through, end) = (
+            Vector2::new(10.0, 0.0),
+            Vector2::new(0.0, 10.0),
+            Vector2::new(-10.0, 0.0),
+        );
+        let outline = Shape::Arc([start, through, end]).outline();
+        assert!(close(outline[0], start, 1e-9));
+        assert!(close(outline[outline.len() - 1], end, 1e-9));
+        assert!(outline.iter().any(|&p| close(p, through, 0.5)));
+        assert!(outline.iter().all(|p| p.y >= -1e-9));
+    }
+}
@@ -0,0 +1,360 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, WebGlTexture};
+
+use crate::{
+    color::Color,
+    gpu::Gpu,
+    raster::RgbaImage,
+    vector::{Rectangle, Vector2},
+    virtual_context::{canvas_from_image, create_canvas_2d},
+};
+
+/// Side in pixels of the square tiles layers are stored in.
+pub const TILE_SIZE: u32 = 256;
+
+/// Border of neighbouring pixels around the texture of a tile, so that filtering
+/// and mipmaps blend across tiles as they would in one big texture.
+pub const TILE_PADDING: u32 = 16;
+
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Canvas a tile is put together with its neighbours on before uploading.
+    static PADDED: (HtmlCanvasElement, CanvasRenderingContext2d) = {
+        let (canvas, context) = create_canvas_2d();
+        canvas.set_width(TILE_SIZE + 2 * TILE_PADDING);
+        canvas.set_height(TILE_SIZE + 2 * TILE_PADDING);
+        (canvas, context)
+    };
+}
+
+/// Versions of a tile and its eight neighbours, a texture is outdated once they change.
+type Sources = [Option<u64>; 9];
+
+pub struct Tile {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    /// Changes whenever the pixels do.
+    version: Cell<u64>,
+    /// Padded copy in the shared GL context for compositing, made once drawn.
+    texture: RefCell<Option<(WebGlTexture, Sources)>>,
+}
+
+impl Tile {
+    fn new() -> Self {
+        let (canvas, context) = create_canvas_2d();
+        canvas.set_width(TILE_SIZE);
+        canvas.set_height(TILE_SIZE);
+        Self {
+            canvas,
+            context,
+            version: Cell::new(next_version()),
+            texture: RefCell::new(None),
+        }
+    }
+
+    pub fn get_canvas(&self) -> &'_ HtmlCanvasElement {
+        &self.canvas
+    }
+
+    fn changed(&self) {
+        self.version.set(next_version());
+    }
+
+    /// Whether the pixels of the document `area`, which lies inside the tile, are all
+    /// fully transparent.
+    fn is_transparent(&self, area: Rectangle) -> bool {
+        let data = self
+            .context
+            .get_image_data(
+                area.coord.x.rem_euclid(TILE_SIZE as f64),
+                area.coord.y.rem_euclid(TILE_SIZE as f64),
+                area.size.x,
+                area.size.y,
+            )
+            .unwrap();
+        data.data().0.chunks(4).all(|pixel| pixel[3] == 0)
+    }
+
+    /// Replaces the pixels with the ones of `source` starting at `(x, y)`, parts
+    /// past its edges become transparent.
+    fn copy_from(&self, source: &HtmlCanvasElement, x: f64, y: f64) {
+        let side = TILE_SIZE as f64;
+        self.context.clear_rect(0.0, 0.0, side, side);
+        self.context
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                source, x, y, side, side, 0.0, 0.0, side, side,
+            )
+            .unwrap();
+        self.changed();
+    }
+}
+
+impl Clone for Tile {
+    fn clone(&self) -> Self {
+        let tile = Self::new();
+        tile.copy_from(&self.canvas, 0.0, 0.0);
+        tile
+    }
+}
+
+impl Drop for Tile {
+    fn drop(&mut self) {
+        if let Some((texture, _)) = self.texture.get_mut() {
+            Gpu::shared().borrow().delete_texture(texture);
+        }
+    }
+}
+
+/// Pixels of a layer as tiles on a grid in document coordinates, only painted tiles
+/// are allocated.
+///
+/// Clones share their tiles until either writes to one, so keeping earlier states of
+/// a layer only costs the tiles changed since.
+#[derive(Clone, Default)]
+pub struct TileGrid {
+    tiles: HashMap<(i32, i32), Rc<Tile>>,
+}
+
+impl TileGrid {
+    /// Tiles holding the painted pixels of `image` placed at `origin`.
+    pub fn from_image(image: &RgbaImage, origin: Vector2) -> Self {
+        let mut grid = Self::default();
+        let source = canvas_from_image(image);
+        let area = Rectangle::new(origin.x, origin.y, image.width as f64, image.height as f64);
+        for key in covering(area) {
+            let rect = match tile_rect(key).intersection(area) {
+                Some(rect) => rect,
+                None => continue,
+            };
+            let (x0, y0) = (
+                (rect.coord.x - origin.x) as u32,
+                (rect.coord.y - origin.y) as u32,
+            );
+            let (x1, y1) = (x0 + rect.size.x as u32, y0 + rect.size.y as u32);
+            let painted = (y0..y1).any(|y| (x0..x1).any(|x| image.get(x, y).get_a() != 0));
+            if painted {
+                grid.copy_tile(key, &source, origin);
+            }
+        }
+        grid
+    }
+
+    /// Allocated tiles with the document area each covers.
+    pub fn iter(&self) -> impl Iterator<Item = (&Tile, Rectangle)> + '_ {
+        self.tiles
+            .iter()
+            .map(|(&key, tile)| (tile.as_ref(), tile_rect(key)))
+    }
+
+    /// Allocated tiles as premultiplied textures with the document area each covers,
+    /// a texture holds `TILE_PADDING` pixels of the neighbouring tiles around it.
+    pub fn textures<'a>(
+        &'a self,
+        gpu: &'a Gpu,
+    ) -> impl Iterator<Item = (WebGlTexture, Rectangle)> + 'a {
+        self.tiles
+            .iter()
+            .map(move |(&key, tile)| (self.texture(gpu, key, tile), tile_rect(key)))
+    }
+
+    fn texture(&self, gpu: &Gpu, (x, y): (i32, i32), tile: &Tile) -> WebGlTexture {
+        let neighbours: Vec<_> = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| (dx, dy, self.tiles.get(&(x + dx, y + dy))))
+            .collect();
+        let mut sources: Sources = [None; 9];
+        for (source, (_, _, neighbour)) in sources.iter_mut().zip(neighbours.iter()) {
+            *source = neighbour.map(|tile| tile.version.get());
+        }
+
+        let mut cached = tile.texture.borrow_mut();
+        if let Some((texture, uploaded)) = cached.as_ref() {
+            if *uploaded == sources {
+                return texture.clone();
+            }
+        }
+        let texture = match cached.take() {
+            Some((texture, _)) => texture,
+            None => gpu.create_texture(),
+        };
+        PADDED.with(|(canvas, context)| {
+            let (side, padding) = (TILE_SIZE as f64, TILE_PADDING as f64);
+            context.clear_rect(0.0, 0.0, canvas.width() as f64, canvas.height() as f64);
+            for (dx, dy, neighbour) in neighbours {
+                if let Some(neighbour) = neighbour {
+                    context
+                        .draw_image_with_html_canvas_element(
+                            &neighbour.canvas,
+                            padding + dx as f64 * side,
+                            padding + dy as f64 * side,
+                        )
+                        .unwrap();
+                }
+            }
+            gpu.upload_premultiplied(&texture, canvas, TILE_PADDING.trailing_zeros());
+        });
+        *cached = Some((texture.clone(), sources));
+        texture
+    }
+
+    /// Copies the pixels of `source`, whose top left pixel is at `origin`, inside
+    /// `area` into the tiles.
+    pub fn store(&mut self, source: &HtmlCanvasElement, origin: Vector2, area: Rectangle) {
+        let extent = Rectangle::new(
+            origin.x,
+            origin.y,
+            source.width() as f64,
+            source.height() as f64,
+        );
+        let area = match area.intersection(extent) {
+            Some(area) => area,
+            None => return,
+        };
+        for key in covering(area) {
+            self.copy_tile(key, source, origin);
+            // Erased tiles are freed, as if never painted
+            let empty = tile_rect(key)
+                .intersection(extent)
+                .is_none_or(|copied| self.tiles[&key].is_transparent(copied));
+            if empty {
+                self.tiles.remove(&key);
+            }
+        }
+    }
+
+    /// Pixels of the document `area`, which has whole pixel coordinates.
+    pub fn to_image(&self, area: Rectangle) -> RgbaImage {
+        let (canvas, context) = create_canvas_2d();
+        canvas.set_width(area.size.x as u32);
+        canvas.set_height(area.size.y as u32);
+        for (tile, rect) in self.iter() {
+            if rect.intersection(area).is_some() {
+                context
+                    .draw_image_with_html_canvas_element(
+                        &tile.canvas,
+                        rect.coord.x - area.coord.x,
+                        rect.coord.y - area.coord.y,
+                    )
+                    .unwrap();
+            }
+        }
+        let data = context
+            .get_image_data(0.0, 0.0, canvas.width() as f64, canvas.height() as f64)
+            .unwrap();
+        RgbaImage::from_data(data.width(), data.height(), data.data().0).unwrap()
+    }
+
+    pub fn fill(&mut self, area: Rectangle, color: Color) {
+        for key in covering(area) {
+            let rect = match tile_rect(key).intersection(area) {
+                Some(rect) => rect,
+                None => continue,
+            };
+            let tile = Rc::make_mut(
+                self.tiles
+                    .entry(key)
+                    .or_insert_with(|| Rc::new(Tile::new())),
+            );
+            let offset = tile_rect(key).coord;
+            tile.context
+                .set_fill_style(&JsValue::from_str(&color.to_style()));
+            tile.context.fill_rect(
+                rect.coord.x - offset.x,
+                rect.coord.y - offset.y,
+                rect.size.x,
+                rect.size.y,
+            );
+            tile.changed();
+        }
+    }
+
+    /// Moves the pixels by `offset`, which is a multiple of `TILE_SIZE`.
+    pub fn shift(&mut self, offset: Vector2) {
+        let side = TILE_SIZE as f64;
+        let (dx, dy) = ((offset.x / side) as i32, (offset.y / side) as i32);
+        self.tiles = self
+            .tiles
+            .drain()
+            .map(|((x, y), tile)| ((x + dx, y + dy), tile))
+            .collect();
+    }
+
+    fn copy_tile(&mut self, key: (i32, i32), source: &HtmlCanvasElement, origin: Vector2) {
+        let tile = self
+            .tiles
+            .entry(key)
+            .or_insert_with(|| Rc::new(Tile::new()));
+        // A tile shared with a clone stays there as it was
+        if Rc::get_mut(tile).is_none() {
+            *tile = Rc::new(Tile::new());
+        }
+        let from = tile_rect(key).coord - origin;
+        tile.copy_from(source, from.x, from.y);
+    }
+}
+
+/// Union of the tiles overlapping `area`.
+pub fn tile_aligned(area: Rectangle) -> Rectangle {
+    let side = TILE_SIZE as f64;
+    let (min, max) = (area.coord, area.coord + area.size);
+    let min = Vector2::new((min.x / side).floor(), (min.y / side).floor()) * side;
+    let max = Vector2::new((max.x / side).ceil(), (max.y / side).ceil()) * side;
+    Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+/// Keys of the tiles overlapping `area`.
+fn covering(area: Rectangle) -> impl Iterator<Item = (i32, i32)> {
+    let side = TILE_SIZE as f64;
+    let (min, max) = (area.coord, area.coord + area.size);
+    let columns = (min.x / side).floor() as i32..(max.x / side).ceil() as i32;
+    let rows = (min.y / side).floor() as i32..(max.y / side).ceil() as i32;
+    rows.flat_map(move |y| columns.clone().map(move |x| (x, y)))
+}
+
+fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+fn tile_rect((x, y): (i32, i32)) -> Rectangle {
+    let side = TILE_SIZE as f64;
+    Rectangle::new(x as f64 * side, y as f64 * side, side, side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_overlapped_tiles() {
+        let keys: Vec<_> = covering(Rectangle::new(-10.0, 250.0, 300.0, 6.0)).collect();
+        assert_eq!(keys, [(-1, 0), (0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn covers_rows_then_columns() {
+        let keys: Vec<_> = covering(Rectangle::new(0.0, 0.0, 300.0, 300.0)).collect();
+        assert_eq!(keys, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn aligns_to_overlapped_tiles() {
+        let aligned = tile_aligned(Rectangle::new(-10.0, 250.0, 300.0, 6.0));
+        assert_eq!(aligned, Rectangle::new(-256.0, 0.0, 768.0, 256.0));
+        let exact = Rectangle::new(256.0, 0.0, 256.0, 256.0);
+        assert_eq!(tile_aligned(exact), exact);
+    }
+
+    #[test]
+    fn edges_stay_in_their_tile() {
+        let keys: Vec<_> = covering(Rectangle::new(256.0, 0.0, 256.0, 256.0)).collect();
+        assert_eq!(keys, [(1, 0)]);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::dodge_burn::{DodgeBurn, ToneMode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Tool {
+    #[default]
+    Brush,
+    Dodge,
+    Burn,
+    Sponge,
+    Airbrush,
+    Spray,
+    /// Moves the handles of drawing assistants instead of painting.
+    Assistant,
+    /// Drags out the part of the canvas to keep.
+    Crop,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 8] = [
+        Tool::Brush,
+        Tool::Airbrush,
+        Tool::Spray,
+        Tool::Dodge,
+        Tool::Burn,
+        Tool::Sponge,
+        Tool::Assistant,
+        Tool::Crop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Brush => "Brush",
+            Tool::Dodge => "Dodge",
+            Tool::Burn => "Burn",
+            Tool::Sponge => "Sponge",
+            Tool::Airbrush => "Airbrush",
+            Tool::Spray => "Spray",
+            Tool::Assistant => "Assistants",
+            Tool::Crop => "Crop",
+        }
+    }
+
+    /// Tools that keep depositing paint while the pointer is held still.
+    pub fn is_continuous(self) -> bool {
+        matches!(self, Tool::Airbrush | Tool::Spray)
+    }
+
+    pub fn tone_mode(self, settings: &DodgeBurn) -> Option<ToneMode> {
+        match self {
+            Tool::Dodge => Some(ToneMode::Dodge),
+            Tool::Burn => Some(ToneMode::Burn),
+            Tool::Sponge => Some(settings.sponge_mode()),
+            _ => None,
+        }
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, Debug)]
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vector2 {
     pub x: f64,
     pub y: f64,
@@ -17,8 +19,43 @@ impl Vector2 {
         let len = self.len();
         Self::new(self.x / len, self.y / len)
     }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Rotates counterclockwise by `angle` radians.
+    pub fn rotate(self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+}
+
+impl Add for Vector2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f64> for Vector2 {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
     pub coord: Vector2,
     pub size: Vector2,
@@ -31,4 +68,222 @@ impl Rectangle {
             size: Vector2::new(w, h),
         }
     }
+
+    /// Smallest rectangle containing both.
+    pub fn union(self, other: Self) -> Self {
+        let (min, max) = (self.coord, self.coord + self.size);
+        let (other_min, other_max) = (other.coord, other.coord + other.size);
+        let min = Vector2::new(min.x.min(other_min.x), min.y.min(other_min.y));
+        let max = Vector2::new(max.x.max(other_max.x), max.y.max(other_max.y));
+        Self {
+            coord: min,
+            size: max - min,
+        }
+    }
+
+    /// Overlap of the two rectangles, `None` if it has no area.
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let (min, max) = (self.coord, self.coord + self.size);
+        let (other_min, other_max) = (other.coord, other.coord + other.size);
+        let min = Vector2::new(min.x.max(other_min.x), min.y.max(other_min.y));
+        let max = Vector2::new(max.x.min(other_max.x), max.y.min(other_max.y));
+        (min.x < max.x && min.y < max.y).then(|| Self {
+            coord: min,
+            size: max - min,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ellipse {
+    pub center: Vector2,
+    pub radii: Vector2,
+    /// Rotation in radians.
+    pub angle: f64,
+}
+
+impl Ellipse {
+    pub fn new(center: Vector2, radii: Vector2, angle: f64) -> Self {
+        Self {
+            center,
+            radii,
+            angle,
+        }
+    }
+
+    /// Maps the ellipse onto the unit circle.
+    pub fn to_unit_circle(&self, point: Vector2) -> Vector2 {
+        let local = (point - self.center).rotate(-self.angle);
+        Vector2::new(
+            local.x / self.radii.x.max(f64::EPSILON),
+            local.y / self.radii.y.max(f64::EPSILON),
+        )
+    }
+
+    /// Inverse of `to_unit_circle`.
+    pub fn from_unit_circle(&self, local: Vector2) -> Vector2 {
+        let scaled = Vector2::new(local.x * self.radii.x, local.y * self.radii.y);
+        self.center + scaled.rotate(self.angle)
+    }
+
+    /// Closed outline, the first point is repeated at the end.
+    pub fn polyline(&self, segments: usize) -> Vec<Vector2> {
+        (0..=segments)
+            .map(|i| {
+                let t = std::f64::consts::TAU * i as f64 / segments as f64;
+                self.from_unit_circle(Vector2::new(t.cos(), t.sin()))
+            })
+            .collect()
+    }
+}
+
+/// 2D affine transform mapping `(x, y)` to `(a x + c y + e, b x + d y + f)`, the
+/// argument order of `CanvasRenderingContext2D.setTransform`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    pub fn translate(offset: Vector2) -> Self {
+        Self {
+            e: offset.x,
+            f: offset.y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn scale(x: f64, y: f64) -> Self {
+        Self {
+            a: x,
+            d: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Applies `self` first and `other` after it.
+    pub fn then(self, other: Self) -> Self {
+        Self {
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            e: other.a * self.e + other.c * self.f + other.e,
+            f: other.b * self.e + other.d * self.f + other.f,
+        }
+    }
+
+    pub fn apply(&self, point: Vector2) -> Vector2 {
+        Vector2::new(
+            self.a * point.x + self.c * point.y + self.e,
+            self.b * point.x + self.d * point.y + self.f,
+        )
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
+
+    /// How much lengths are scaled on average.
+    pub fn scale_factor(&self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vector2, expected: Vector2) {
+        assert!(
+            (actual - expected).len() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn sample() -> Transform {
+        Transform::translate(Vector2::new(-30.0, 12.0))
+            .then(Transform::rotate(0.7))
+            .then(Transform::scale(2.5, -1.5))
+            .then(Transform::translate(Vector2::new(100.0, 50.0)))
+    }
+
+    #[test]
+    fn then_inverse_is_identity() {
+        let t = sample();
+        let identity = t.then(t.inverse().unwrap());
+        for point in [Vector2::new(0.0, 0.0), Vector2::new(-17.0, 250.5)] {
+            assert_close(identity.apply(point), point);
+            assert_close(t.inverse().unwrap().then(t).apply(point), point);
+        }
+    }
+
+    #[test]
+    fn then_applies_self_first() {
+        let point = Vector2::new(3.0, 4.0);
+        let move_then_scale =
+            Transform::translate(Vector2::new(1.0, 0.0)).then(Transform::scale(2.0, 2.0));
+        assert_close(move_then_scale.apply(point), Vector2::new(8.0, 8.0));
+        let scale_then_move =
+            Transform::scale(2.0, 2.0).then(Transform::translate(Vector2::new(1.0, 0.0)));
+        assert_close(scale_then_move.apply(point), Vector2::new(7.0, 8.0));
+
+        let t = sample();
+        let u = Transform::rotate(-1.2).then(Transform::translate(Vector2::new(5.0, 5.0)));
+        assert_close(t.then(u).apply(point), u.apply(t.apply(point)));
+    }
+
+    #[test]
+    fn rotation_turns_x_towards_y() {
+        let quarter = Transform::rotate(std::f64::consts::FRAC_PI_2);
+        assert_close(
+            quarter.apply(Vector2::new(1.0, 0.0)),
+            Vector2::new(0.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn singular_transforms_have_no_inverse() {
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+        assert!((sample().scale_factor() - (2.5f64 * 1.5).sqrt()).abs() < 1e-9);
+    }
 }
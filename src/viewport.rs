@@ -0,0 +1,317 @@
+use crate::vector::{Rectangle, Transform, Vector2};
+
+pub const MIN_ZOOM: f64 = 0.02;
+pub const MAX_ZOOM: f64 = 64.0;
+
+/// Margin around the document when it's fit to the window, in screen pixels.
+const FIT_MARGIN: f64 = 20.0;
+
+/// Rotation step the view snaps to.
+pub const ROTATION_STEP: f64 = std::f64::consts::PI / 12.0;
+
+/// Rotations closer than this to a multiple of `ROTATION_STEP` snap to it.
+const ROTATION_SNAP: f64 = 0.07;
+
+/// Part of the screen showing the document. The view is centered on a document
+/// point, zoomed, rotated and optionally mirrored around the middle of the screen
+/// without touching the document pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Document point in the middle of the screen.
+    pub center: Vector2,
+    pub zoom: f64,
+    /// Clockwise rotation on screen in radians.
+    pub rotation: f64,
+    /// Mirrors the view horizontally.
+    pub flipped: bool,
+    /// Screen size in pixels.
+    pub size: Vector2,
+}
+
+impl Viewport {
+    pub fn new(size: Vector2) -> Self {
+        Self {
+            center: size * 0.5,
+            zoom: 1.0,
+            rotation: 0.0,
+            flipped: false,
+            size,
+        }
+    }
+
+    /// Maps document coordinates to screen pixels.
+    pub fn transform(&self) -> Transform {
+        Transform::translate(self.center * -1.0)
+            .then(self.linear())
+            .then(Transform::translate(self.size * 0.5))
+    }
+
+    fn linear(&self) -> Transform {
+        let mirror = if self.flipped { -1.0 } else { 1.0 };
+        Transform::scale(mirror, 1.0)
+            .then(Transform::rotate(self.rotation))
+            .then(Transform::scale(self.zoom, self.zoom))
+    }
+
+    pub fn to_document(&self, screen: Vector2) -> Vector2 {
+        self.transform()
+            .inverse()
+            .unwrap_or(Transform::IDENTITY)
+            .apply(screen)
+    }
+
+    pub fn to_screen(&self, document: Vector2) -> Vector2 {
+        self.transform().apply(document)
+    }
+
+    /// Smallest document-aligned rectangle containing the screen, as its corners.
+    pub fn visible_bounds(&self) -> (Vector2, Vector2) {
+        let corners = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(self.size.x, 0.0),
+            self.size,
+            Vector2::new(0.0, self.size.y),
+        ]
+        .map(|corner| self.to_document(corner));
+        corners
+            .iter()
+            .fold((corners[0], corners[0]), |(min, max), p| {
+                (
+                    Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+                    Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            })
+    }
+
+    /// Zooms by `factor` keeping the document point under `anchor` in place.
+    pub fn zoom_at(&mut self, anchor: Vector2, factor: f64) {
+        let fixed = self.to_document(anchor);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.keep(fixed, anchor);
+    }
+
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Moves the view by `delta` screen pixels.
+    pub fn pan(&mut self, delta: Vector2) {
+        let middle = self.size * 0.5;
+        self.center = self.to_document(middle - delta);
+    }
+
+    /// Rotates around the middle of the screen, snapping near multiples of `ROTATION_STEP`.
+    /// The rotation is kept in `(-π, π]`.
+    pub fn set_rotation(&mut self, rotation: f64) {
+        let snapped = (rotation / ROTATION_STEP).round() * ROTATION_STEP;
+        let rotation = if (rotation - snapped).abs() < ROTATION_SNAP {
+            snapped
+        } else {
+            rotation
+        };
+        let half_turn = std::f64::consts::PI;
+        self.rotation = half_turn - (half_turn - rotation).rem_euclid(2.0 * half_turn);
+    }
+
+    pub fn set_flipped(&mut self, flipped: bool) {
+        self.flipped = flipped;
+    }
+
+    /// Resizes the screen keeping the view centered on the same document point.
+    pub fn set_size(&mut self, size: Vector2) {
+        self.size = size;
+    }
+
+    /// Shows the whole document in the middle of the screen, keeping the rotation.
+    pub fn fit(&mut self, document: Rectangle) {
+        let (min, max) = (document.coord, document.coord + document.size);
+        let corners = [
+            min,
+            Vector2::new(max.x, min.y),
+            max,
+            Vector2::new(min.x, max.y),
+        ];
+        // Extent of the document rotated on screen at zoom 1
+        let rotation = Transform::rotate(self.rotation);
+        let (mut min, mut max) = (
+            Vector2::new(f64::INFINITY, f64::INFINITY),
+            Vector2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        );
+        for corner in corners {
+            let p = rotation.apply(corner);
+            min = Vector2::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vector2::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let extent = max - min;
+
+        let margin = FIT_MARGIN * 2.0;
+        self.set_zoom(((self.size.x - margin) / extent.x).min((self.size.y - margin) / extent.y));
+        self.center = document.coord + document.size * 0.5;
+    }
+
+    /// Shows the document pixel for pixel in the middle of the screen.
+    pub fn actual_size(&mut self, document: Rectangle) {
+        self.zoom = 1.0;
+        self.center = document.coord + document.size * 0.5;
+    }
+
+    /// Moves the view so that the document point `fixed` is at `screen`.
+    fn keep(&mut self, fixed: Vector2, screen: Vector2) {
+        let inverse = self.linear().inverse().unwrap_or(Transform::IDENTITY);
+        let offset = inverse.apply(screen - self.size * 0.5);
+        self.center = fixed - offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Vector2, expected: Vector2) {
+        assert!(
+            (actual - expected).len() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn zoomed() -> Viewport {
+        let mut viewport = Viewport::new(Vector2::new(800.0, 600.0));
+        viewport.center = Vector2::new(120.0, -40.0);
+        viewport.set_zoom(2.5);
+        viewport
+    }
+
+    #[test]
+    fn screen_and_document_round_trip() {
+        let viewport = zoomed();
+        for point in [Vector2::new(0.0, 0.0), Vector2::new(-13.5, 987.25)] {
+            assert_close(viewport.to_document(viewport.to_screen(point)), point);
+            assert_close(viewport.to_screen(viewport.to_document(point)), point);
+        }
+    }
+
+    #[test]
+    fn center_is_in_the_middle_and_zoom_scales() {
+        let viewport = zoomed();
+        assert_close(
+            viewport.to_screen(viewport.center),
+            Vector2::new(400.0, 300.0),
+        );
+        assert_close(
+            viewport.to_screen(viewport.center + Vector2::new(10.0, 4.0)),
+            Vector2::new(425.0, 310.0),
+        );
+    }
+
+    #[test]
+    fn zoom_keeps_the_anchor() {
+        let mut viewport = zoomed();
+        let anchor = Vector2::new(100.0, 500.0);
+        let fixed = viewport.to_document(anchor);
+        viewport.zoom_at(anchor, 3.0);
+        assert!((viewport.zoom - 7.5).abs() < 1e-9);
+        assert_close(viewport.to_screen(fixed), anchor);
+
+        viewport.zoom_at(anchor, 1e6);
+        assert_eq!(viewport.zoom, MAX_ZOOM);
+    }
+
+    fn rotated(rotation: f64, flipped: bool) -> Viewport {
+        let mut viewport = zoomed();
+        viewport.set_rotation(rotation);
+        viewport.set_flipped(flipped);
+        viewport
+    }
+
+    #[test]
+    fn rotated_and_flipped_views_round_trip() {
+        let point = Vector2::new(-13.5, 987.25);
+        for rotation in [0.4, 2.0, -2.9] {
+            for flipped in [false, true] {
+                let viewport = rotated(rotation, flipped);
+                assert_close(viewport.to_document(viewport.to_screen(point)), point);
+                assert_close(viewport.to_screen(viewport.to_document(point)), point);
+                assert_close(
+                    viewport.to_screen(viewport.center),
+                    Vector2::new(400.0, 300.0),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_turns_clockwise_and_flip_mirrors() {
+        let offset = Vector2::new(10.0, 0.0);
+        let middle = Vector2::new(400.0, 300.0);
+
+        let viewport = rotated(std::f64::consts::FRAC_PI_2, false);
+        assert_close(
+            viewport.to_screen(viewport.center + offset),
+            middle + Vector2::new(0.0, 25.0),
+        );
+
+        let viewport = rotated(0.0, true);
+        assert_close(
+            viewport.to_screen(viewport.center + offset),
+            middle + Vector2::new(-25.0, 0.0),
+        );
+        // Mirrored first, then turned on screen
+        let viewport = rotated(std::f64::consts::FRAC_PI_2, true);
+        assert_close(
+            viewport.to_screen(viewport.center + offset),
+            middle + Vector2::new(0.0, -25.0),
+        );
+    }
+
+    fn assert_rotation(viewport: &Viewport, expected: f64) {
+        assert!(
+            (viewport.rotation - expected).abs() < 1e-9,
+            "{} != {}",
+            viewport.rotation,
+            expected
+        );
+    }
+
+    #[test]
+    fn rotation_snaps_to_steps() {
+        let mut viewport = zoomed();
+        viewport.set_rotation(15f64.to_radians() + 0.05);
+        assert_rotation(&viewport, ROTATION_STEP);
+        viewport.set_rotation(-30f64.to_radians() - 0.05);
+        assert_rotation(&viewport, -2.0 * ROTATION_STEP);
+
+        let free = 15f64.to_radians() + 0.1;
+        viewport.set_rotation(free);
+        assert_rotation(&viewport, free);
+    }
+
+    #[test]
+    fn rotation_wraps_into_a_turn() {
+        use std::f64::consts::PI;
+
+        let mut viewport = zoomed();
+        viewport.set_rotation(PI);
+        assert_rotation(&viewport, PI);
+        viewport.set_rotation(-PI);
+        assert_rotation(&viewport, PI);
+        viewport.set_rotation(1.5 * PI + 0.4);
+        assert_rotation(&viewport, -0.5 * PI + 0.4);
+        viewport.set_rotation(-1.5 * PI - 0.4);
+        assert_rotation(&viewport, 0.5 * PI - 0.4);
+        assert!(viewport.rotation > -PI && viewport.rotation <= PI);
+    }
+
+    #[test]
+    fn pan_moves_by_screen_pixels() {
+        let mut viewport = zoomed();
+        let point = Vector2::new(50.0, 60.0);
+        let before = viewport.to_screen(point);
+        viewport.pan(Vector2::new(30.0, -20.0));
+        assert_close(
+            viewport.to_screen(point),
+            before + Vector2::new(30.0, -20.0),
+        );
+    }
+}
@@ -1,27 +1,62 @@
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    rc::Rc,
+};
+
 use gloo::utils::document;
-use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, WebGl2RenderingContext, WebGlTexture};
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{
+    CanvasPattern, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, ImageData,
+    WebGlTexture,
+};
 
 use crate::{
+    brush::{Dab, PaintMode},
     color::Color,
-    shaders::{
-        checkerboard::CheckerboardShader, copy_image::CopyImageShader, hsv_circle::HsvCircleShader,
-        load_texture_from_canvas,
-    },
-    vector::{Rectangle, Vector2},
+    dodge_burn::{DodgeBurn, ToneMode},
+    gpu::{Gpu, RenderTarget},
+    raster::RgbaImage,
+    tiles::{TileGrid, TILE_PADDING, TILE_SIZE},
+    vector::{Rectangle, Transform, Vector2},
 };
 
+/// Darker cells of the checkerboard behind transparent pixels.
+const CHECKER_DARK: Color = Color::new(191, 191, 191, 255);
+
+/// How `VirtualContext::fill_dabs` draws a dab: a custom `tip` instead of an ellipse,
+/// a `pattern` in canvas space instead of the dab color, a `grain` coverage
+/// texture with its scale, tiled in canvas space, and the paint `mode`.
+#[derive(Clone, Copy, Default)]
+pub struct DabStyle<'a> {
+    pub tip: Option<&'a HtmlCanvasElement>,
+    pub pattern: Option<&'a HtmlCanvasElement>,
+    pub grain: Option<(&'a HtmlCanvasElement, f64)>,
+    pub mode: PaintMode,
+}
+
 pub struct VirtualContext {
     canvas_2d: HtmlCanvasElement,
     context_2d: CanvasRenderingContext2d,
-    canvas_gl: HtmlCanvasElement,
-    context_gl: WebGl2RenderingContext,
 
-    hsv_circle: HsvCircleShader,
-    copy_image: CopyImageShader,
-    checkerboard: CheckerboardShader,
+    gpu: Rc<RefCell<Gpu>>,
+    /// Copy of the 2D canvas in the shared GL context, brought up to date only when GL
+    /// reads it.
+    texture: WebGlTexture,
+    texture_size: Cell<(u32, u32)>,
+    /// Where GL passes on this surface render.
+    target: RenderTarget,
+    /// Canvas pixels drawn since the texture was last updated.
+    stale: Cell<Option<Rectangle>>,
+    /// Current transform of the 2D context, tells where drawing lands.
+    transform: Cell<Transform>,
 
-    texture_for_swaps: WebGlTexture,
+    scratch: HtmlCanvasElement,
+    scratch_context: CanvasRenderingContext2d,
+    buffer: HtmlCanvasElement,
+    buffer_context: CanvasRenderingContext2d,
+
+    /// Document position of the top left pixel, drawing happens in document coordinates.
+    origin: Vector2,
 }
 
 impl VirtualContext {
@@ -34,40 +69,28 @@ impl VirtualContext {
             .unwrap()
             .dyn_into::<CanvasRenderingContext2d>()
             .unwrap();
-        let canvas_gl: HtmlCanvasElement = document()
-            .create_element("canvas")
-            .unwrap()
-            .unchecked_into();
-        canvas_gl.set_width(width);
-        canvas_gl.set_height(height);
-        let context_gl = canvas_gl
-            .get_context("webgl2")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<WebGl2RenderingContext>()
-            .unwrap();
-        Self {
-            texture_for_swaps: context_gl.create_texture().unwrap(),
-            hsv_circle: HsvCircleShader::new(
-                &context_gl,
-                canvas_gl.width() as i32,
-                canvas_gl.height() as i32,
-            ),
-            copy_image: CopyImageShader::new(
-                &context_gl,
-                canvas_gl.width() as i32,
-                canvas_gl.height() as i32,
-            ),
-            checkerboard: CheckerboardShader::new(
-                &context_gl,
-                canvas_gl.width() as i32,
-                canvas_gl.height() as i32,
-            ),
+        let gpu = Gpu::shared();
+        let texture = gpu.borrow().create_texture();
+        let target = gpu.borrow().create_target();
+        let (scratch, scratch_context) = create_canvas_2d();
+        let (buffer, buffer_context) = create_canvas_2d();
+        let context = Self {
+            gpu,
+            texture,
+            texture_size: Cell::new((0, 0)),
+            target,
+            stale: Cell::new(None),
+            transform: Cell::new(Transform::IDENTITY),
+            scratch,
+            scratch_context,
+            buffer,
+            buffer_context,
             canvas_2d: canvas,
             context_2d,
-            canvas_gl,
-            context_gl,
-        }
+            origin: Vector2::new(0.0, 0.0),
+        };
+        context.touch_all();
+        context
     }
 
     pub fn new_independent(width: u32, height: u32) -> Self {
@@ -79,19 +102,72 @@ impl VirtualContext {
     }
 
     pub fn hsv_circle(&self, x: f64, y: f64, r: f64) {
-        self.hsv_circle
-            .draw(&self.context_gl, x as i32, y as i32, r as i32);
-        self.flush_gl_to_2d();
+        let gpu = self.begin_gl(true);
+        gpu.hsv_circle.draw(gpu.gl(), x as i32, y as i32, r as i32);
+        self.flush_gl_to_2d(&gpu);
+        self.touch_all();
     }
 
     pub fn checkerboard(&self, cell_size: f64, color_a: Color, color_b: Color) {
-        self.checkerboard.draw(
-            &self.context_gl,
+        let gpu = self.begin_gl(false);
+        gpu.checkerboard.draw(
+            gpu.gl(),
             Vector2::new(cell_size as f64, cell_size as f64),
             color_a,
             color_b,
         );
-        self.flush_gl_to_2d();
+        self.flush_gl_to_2d(&gpu);
+        self.touch_all();
+    }
+
+    /// Draws the tile grids bottom to top through `transform` over a checkerboard in
+    /// one GL pass, the canvas gets the result once at the end.
+    pub fn composite<'a>(
+        &self,
+        cell_size: f64,
+        grids: impl IntoIterator<Item = &'a TileGrid>,
+        transform: &Transform,
+        smooth: bool,
+    ) {
+        let gpu = self.begin_gl(false);
+        let gl = gpu.gl();
+        gpu.checkerboard.draw(
+            gl,
+            Vector2::new(cell_size, cell_size),
+            CHECKER_DARK,
+            Color::WHITE,
+        );
+        gpu.composite
+            .begin(gl, &transform.then(self.base_transform()));
+        // Tile textures are padded with their neighbours
+        let side = (TILE_SIZE + 2 * TILE_PADDING) as f64;
+        let inner = TILE_PADDING as f64 / side;
+        let source = Rectangle::new(inner, inner, 1.0 - 2.0 * inner, 1.0 - 2.0 * inner);
+        for grid in grids {
+            for (texture, rect) in grid.textures(&gpu) {
+                gpu.composite.draw(gl, &texture, rect, source, smooth);
+            }
+        }
+        gpu.composite.end(gl);
+        self.flush_gl_to_2d(&gpu);
+        self.touch_all();
+    }
+
+    pub fn dodge_burn(&self, center: Vector2, r: f64, mode: ToneMode, settings: DodgeBurn) {
+        // The shader reads the texture and writes every pixel
+        let gpu = self.begin_gl(false);
+        self.sync_texture(&gpu);
+        gpu.dodge_burn.draw(
+            gpu.gl(),
+            center - self.origin,
+            r,
+            mode,
+            settings,
+            &self.texture,
+        );
+        self.replace_2d_with_gl(&gpu);
+        // Outside the circle the pixels went through GL and back unchanged
+        self.touch_circle(center, r);
     }
 
     pub fn line(&self, x0: f64, y0: f64, x1: f64, y1: f64, width: f64, color: Color) {
@@ -103,11 +179,108 @@ impl VirtualContext {
         self.context_2d.line_to(x1, y1);
         self.context_2d.stroke();
         self.context_2d.close_path();
-        self.flush_2d_to_gl();
+        // The ends with a margin of the line width, wide enough for any cap or angle
+        let min = Vector2::new(x0.min(x1), y0.min(y1)) - Vector2::new(width, width);
+        let max = Vector2::new(x0.max(x1), y0.max(y1)) + Vector2::new(width, width);
+        self.touch(Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y));
+    }
+
+    pub fn stroke_polylines(&self, polylines: &[Vec<Vector2>], width: f64, color: Color) {
+        self.context_2d.begin_path();
+        self.context_2d
+            .set_stroke_style(&JsValue::from_str(&color.to_style()));
+        self.context_2d.set_line_width(width);
+        for polyline in polylines {
+            if let Some((first, rest)) = polyline.split_first() {
+                self.context_2d.move_to(first.x, first.y);
+                for point in rest {
+                    self.context_2d.line_to(point.x, point.y);
+                }
+            }
+        }
+        self.context_2d.stroke();
+        self.touch_all();
+    }
+
+    /// Transform of everything drawn next into document coordinates, see `reset_transform`.
+    pub fn set_transform(&self, t: &Transform) {
+        let t = t.then(self.base_transform());
+        self.context_2d
+            .set_transform(t.a, t.b, t.c, t.d, t.e, t.f)
+            .unwrap();
+        self.transform.set(t);
+    }
+
+    /// From document coordinates to pixels of the canvas.
+    fn base_transform(&self) -> Transform {
+        Transform::translate(self.origin * -1.0)
+    }
+
+    pub fn origin(&self) -> Vector2 {
+        self.origin
+    }
+
+    /// Moves the canvas over the document without changing its pixels.
+    pub fn set_origin(&mut self, origin: Vector2) {
+        self.origin = origin;
+        self.reset_transform();
+    }
+
+    pub fn reset_transform(&self) {
+        self.set_transform(&Transform::IDENTITY);
+    }
+
+    pub fn set_image_smoothing(&self, enabled: bool) {
+        self.context_2d.set_image_smoothing_enabled(enabled);
+    }
+
+    /// Fills everything except the quadrilateral.
+    pub fn fill_outside(&self, corners: &[Vector2; 4], color: Color) {
+        self.context_2d.begin_path();
+        self.context_2d
+            .set_fill_style(&JsValue::from_str(&color.to_style()));
+        self.context_2d.rect(
+            self.origin.x,
+            self.origin.y,
+            self.width() as f64,
+            self.height() as f64,
+        );
+        self.context_2d.move_to(corners[0].x, corners[0].y);
+        for corner in &corners[1..] {
+            self.context_2d.line_to(corner.x, corner.y);
+        }
+        self.context_2d.close_path();
+        self.context_2d
+            .fill_with_canvas_winding_rule(web_sys::CanvasWindingRule::Evenodd);
+        self.touch_all();
+    }
+
+    pub fn fill_rect(&self, rect: Rectangle, color: Color) {
+        self.context_2d
+            .set_fill_style(&JsValue::from_str(&color.to_style()));
+        self.context_2d
+            .fill_rect(rect.coord.x, rect.coord.y, rect.size.x, rect.size.y);
+        self.touch(rect);
+    }
+
+    /// Draws small labels, each with its baseline starting at the position.
+    pub fn fill_labels(&self, labels: &[(String, Vector2)], color: Color) {
+        self.context_2d.set_font("9px sans-serif");
+        self.context_2d
+            .set_fill_style(&JsValue::from_str(&color.to_style()));
+        for (text, position) in labels {
+            self.context_2d
+                .fill_text(text, position.x, position.y)
+                .unwrap();
+        }
+        self.touch_all();
     }
 
     pub fn clear(&self, color: Color) {
         self.context_2d.save();
+        self.context_2d
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
         self.context_2d
             .set_global_composite_operation("copy")
             .unwrap();
@@ -120,7 +293,7 @@ impl VirtualContext {
             self.canvas_2d.height() as f64,
         );
         self.context_2d.restore();
-        self.flush_2d_to_gl();
+        self.touch_all();
     }
 
     pub fn fill_circle(&self, x0: f64, y0: f64, r: f64, color: Color) {
@@ -132,7 +305,188 @@ impl VirtualContext {
             .unwrap();
         self.context_2d.fill();
         self.context_2d.close_path();
-        self.flush_2d_to_gl();
+        self.touch_circle(Vector2::new(x0, y0), r);
+    }
+
+    pub fn fill_soft_circle(&self, x0: f64, y0: f64, r: f64, color: Color) {
+        let gradient = self
+            .context_2d
+            .create_radial_gradient(x0, y0, 0.0, x0, y0, r)
+            .unwrap();
+        gradient.add_color_stop(0.0, &color.to_style()).unwrap();
+        gradient
+            .add_color_stop(1.0, &color.with_alpha(0).to_style())
+            .unwrap();
+        self.context_2d.begin_path();
+        self.context_2d.set_fill_style(&gradient);
+        self.context_2d
+            .arc(x0, y0, r, 0.0, std::f64::consts::PI * 2.0)
+            .unwrap();
+        self.context_2d.fill();
+        self.context_2d.close_path();
+        self.touch_circle(Vector2::new(x0, y0), r);
+    }
+
+    /// Draws brush dabs in the given style, see `DabStyle`.
+    pub fn fill_dabs(&self, dabs: &[Dab], style: DabStyle) {
+        // Grained dabs are painted on the buffer over their bounding box first
+        let extent = dabs_extent(dabs);
+        let target = match (style.grain, extent) {
+            (Some(_), None) => return,
+            (Some(_), Some(extent)) => {
+                self.buffer.set_width(extent.size.x as u32);
+                self.buffer.set_height(extent.size.y as u32);
+                self.buffer_context
+                    .set_transform(1.0, 0.0, 0.0, 1.0, -extent.coord.x, -extent.coord.y)
+                    .unwrap();
+                &self.buffer_context
+            }
+            (None, _) => {
+                self.context_2d
+                    .set_global_composite_operation(style.mode.composite_operation())
+                    .unwrap();
+                &self.context_2d
+            }
+        };
+
+        let pattern = style.pattern.map(|canvas| {
+            target
+                .create_pattern_with_html_canvas_element(canvas, "repeat")
+                .unwrap()
+                .unwrap()
+        });
+
+        for dab in dabs {
+            let shape = dab.shape;
+            if shape.radii.x <= 0.0 || shape.radii.y <= 0.0 {
+                continue;
+            }
+            match (style.tip, &pattern) {
+                (Some(tip), _) => self.fill_tip_dab(target, dab, tip, pattern.as_ref()),
+                (None, Some(pattern)) => {
+                    target.save();
+                    target.set_global_alpha(dab.color.get_a() as f64 / 255.0);
+                    target.set_fill_style(pattern);
+                    target.begin_path();
+                    target
+                        .ellipse(
+                            shape.center.x,
+                            shape.center.y,
+                            shape.radii.x,
+                            shape.radii.y,
+                            shape.angle,
+                            0.0,
+                            std::f64::consts::PI * 2.0,
+                        )
+                        .unwrap();
+                    target.fill();
+                    target.close_path();
+                    target.restore();
+                }
+                (None, None) => fill_round_dab(target, dab),
+            }
+        }
+
+        if let (Some((grain, scale)), Some(extent)) = (style.grain, extent) {
+            let grain = target
+                .create_pattern_with_html_canvas_element(grain, "repeat")
+                .unwrap()
+                .unwrap();
+            target
+                .set_global_composite_operation("destination-in")
+                .unwrap();
+            // The grain pattern stays anchored to the document, wherever the buffer is
+            target.scale(scale, scale).unwrap();
+            target.set_fill_style(&grain);
+            target.fill_rect(
+                extent.coord.x / scale,
+                extent.coord.y / scale,
+                extent.size.x / scale,
+                extent.size.y / scale,
+            );
+            self.context_2d
+                .set_global_composite_operation(style.mode.composite_operation())
+                .unwrap();
+            self.context_2d
+                .draw_image_with_html_canvas_element(&self.buffer, extent.coord.x, extent.coord.y)
+                .unwrap();
+        }
+
+        self.context_2d
+            .set_global_composite_operation("source-over")
+            .unwrap();
+        for dab in dabs {
+            let shape = dab.shape;
+            self.touch_circle(shape.center, shape.radii.x.max(shape.radii.y));
+        }
+    }
+
+    /// Tints the tip in the scratch canvas and stamps it scaled so its longest side is
+    /// the dab diameter.
+    fn fill_tip_dab(
+        &self,
+        target: &CanvasRenderingContext2d,
+        dab: &Dab,
+        tip: &HtmlCanvasElement,
+        pattern: Option<&CanvasPattern>,
+    ) {
+        let shape = dab.shape;
+        let (w, h) = (tip.width() as f64, tip.height() as f64);
+        let k = 2.0 * shape.radii.x / w.max(h);
+        let roundness = shape.radii.y / shape.radii.x;
+
+        self.scratch.set_width(tip.width());
+        self.scratch.set_height(tip.height());
+        let scratch = &self.scratch_context;
+        scratch
+            .draw_image_with_html_canvas_element(tip, 0.0, 0.0)
+            .unwrap();
+        scratch.set_global_composite_operation("source-in").unwrap();
+        match pattern {
+            Some(pattern) => {
+                // Inverse of the stamp transform below, so the pattern stays in canvas space
+                scratch.translate(w / 2.0, h / 2.0).unwrap();
+                scratch.scale(1.0 / k, 1.0 / (k * roundness)).unwrap();
+                scratch.rotate(-shape.angle).unwrap();
+                scratch.translate(-shape.center.x, -shape.center.y).unwrap();
+                scratch.set_fill_style(pattern);
+                let r = shape.radii.x.max(shape.radii.y) * 2.0;
+                scratch.fill_rect(shape.center.x - r, shape.center.y - r, 2.0 * r, 2.0 * r);
+            }
+            None => {
+                scratch.set_fill_style(&JsValue::from_str(&dab.color.to_style()));
+                scratch.fill_rect(0.0, 0.0, w, h);
+            }
+        }
+
+        target.save();
+        if pattern.is_some() {
+            target.set_global_alpha(dab.color.get_a() as f64 / 255.0);
+        }
+        target.translate(shape.center.x, shape.center.y).unwrap();
+        target.rotate(shape.angle).unwrap();
+        target.scale(k, k * roundness).unwrap();
+        target
+            .draw_image_with_html_canvas_element(&self.scratch, -w / 2.0, -h / 2.0)
+            .unwrap();
+        target.restore();
+    }
+
+    pub fn fill_circles(&self, centers: &[Vector2], r: f64, color: Color) {
+        self.context_2d.begin_path();
+        self.context_2d
+            .set_fill_style(&JsValue::from_str(&color.to_style()));
+        for center in centers {
+            self.context_2d.move_to(center.x + r, center.y);
+            self.context_2d
+                .arc(center.x, center.y, r, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+        }
+        self.context_2d.fill();
+        self.context_2d.close_path();
+        for &center in centers {
+            self.touch_circle(center, r);
+        }
     }
 
     pub fn draw_circle(&self, x0: f64, y0: f64, r: f64, width: f64) {
@@ -145,14 +499,19 @@ impl VirtualContext {
             .unwrap();
         self.context_2d.stroke();
         self.context_2d.close_path();
-        self.flush_2d_to_gl();
+        self.touch_circle(Vector2::new(x0, y0), r + width);
     }
 
     pub fn draw_image(&self, image: &HtmlCanvasElement) {
         self.context_2d
             .draw_image_with_html_canvas_element(image, 0.0, 0.0)
             .unwrap();
-        self.flush_2d_to_gl();
+        self.touch(Rectangle::new(
+            0.0,
+            0.0,
+            image.width() as f64,
+            image.height() as f64,
+        ));
     }
 
     pub fn draw_image_bounded(&self, image: &HtmlCanvasElement, bounds: Rectangle) {
@@ -165,21 +524,73 @@ impl VirtualContext {
                 bounds.size.y,
             )
             .unwrap();
-        self.flush_2d_to_gl();
+        self.touch(bounds);
+    }
+
+    /// Draws each image into its rectangle, e.g. the tiles of layers.
+    pub fn draw_images_bounded<'a>(
+        &self,
+        images: impl IntoIterator<Item = (&'a HtmlCanvasElement, Rectangle)>,
+    ) {
+        for (image, bounds) in images {
+            self.context_2d
+                .draw_image_with_html_canvas_element_and_dw_and_dh(
+                    image,
+                    bounds.coord.x,
+                    bounds.coord.y,
+                    bounds.size.x,
+                    bounds.size.y,
+                )
+                .unwrap();
+            self.touch(bounds);
+        }
     }
 
     pub fn get_canvas(&self) -> &'_ HtmlCanvasElement {
         &self.canvas_2d
     }
 
+    pub fn get_image(&self) -> RgbaImage {
+        let data = self
+            .context_2d
+            .get_image_data(0.0, 0.0, self.width() as f64, self.height() as f64)
+            .unwrap();
+        RgbaImage::from_data(data.width(), data.height(), data.data().0).unwrap()
+    }
+
+    pub fn put_image(&self, image: &RgbaImage) {
+        let data = ImageData::new_with_u8_clamped_array(Clamped(&image.data), image.width).unwrap();
+        self.context_2d.put_image_data(&data, 0.0, 0.0).unwrap();
+        self.touch_all();
+    }
+
     pub fn set_size(&mut self, width: u32, height: u32) {
+        self.resize(width, height, Vector2::new(0.0, 0.0));
+    }
+
+    /// Changes the size keeping the pixels, moved by `offset`.
+    pub fn resize(&mut self, width: u32, height: u32, offset: Vector2) {
+        // Empty canvases can't be drawn, e.g. the view of a collapsed panel
+        let keep = self.width() > 0 && self.height() > 0 && width > 0 && height > 0;
+        if keep {
+            self.scratch.set_width(self.width());
+            self.scratch.set_height(self.height());
+            self.scratch_context
+                .draw_image_with_html_canvas_element(&self.canvas_2d, 0.0, 0.0)
+                .unwrap();
+        }
+
         self.canvas_2d.set_width(width);
-        self.canvas_gl.set_width(width);
         self.canvas_2d.set_height(height);
-        self.canvas_gl.set_height(height);
-        self.checkerboard.set_size(width as i32, height as i32);
-        self.hsv_circle.set_size(width as i32, height as i32);
-        self.copy_image.set_size(width as i32, height as i32);
+
+        if keep {
+            self.context_2d
+                .draw_image_with_html_canvas_element(&self.scratch, offset.x, offset.y)
+                .unwrap();
+        }
+        // Resizing the canvas reset its transform
+        self.reset_transform();
+        self.touch_all();
     }
 
     pub fn width(&self) -> u32 {
@@ -190,21 +601,216 @@ impl VirtualContext {
         self.get_canvas().height()
     }
 
-    fn flush_2d_to_gl(&self) {
-        load_texture_from_canvas(&self.context_gl, &self.texture_for_swaps, &self.canvas_2d);
-        self.copy_image.draw(
-            &self.context_gl,
-            0,
-            0,
-            self.canvas_gl.width() as i32,
-            self.canvas_gl.height() as i32,
-            &self.texture_for_swaps,
+    /// Gets the render target of this surface ready for drawing, starting from its
+    /// pixels if `keep` is set.
+    fn begin_gl(&self, keep: bool) -> RefMut<'_, Gpu> {
+        let mut gpu = self.gpu.borrow_mut();
+        if keep {
+            self.sync_texture(&gpu);
+        }
+        gpu.begin(
+            &self.target,
+            keep.then_some(&self.texture),
+            self.width(),
+            self.height(),
         );
+        gpu
     }
 
-    fn flush_gl_to_2d(&self) {
+    /// Uploads what changed on the 2D canvas to the texture.
+    fn sync_texture(&self, gpu: &Gpu) {
+        let stale = match self.stale.take() {
+            Some(stale) => stale,
+            None => return,
+        };
+        let size = (self.width(), self.height());
+        if self.texture_size.replace(size) == size {
+            gpu.upload_region(&self.texture, &self.canvas_2d, stale);
+        } else {
+            gpu.upload(&self.texture, &self.canvas_2d);
+        }
+    }
+
+    /// Marks `area`, under the current transform, as changed on the 2D canvas.
+    fn touch(&self, area: Rectangle) {
+        let t = self.transform.get();
+        let (min, max) = (area.coord, area.coord + area.size);
+        let corners = [
+            min,
+            Vector2::new(max.x, min.y),
+            max,
+            Vector2::new(min.x, max.y),
+        ]
+        .map(|corner| t.apply(corner));
+        let (min, max) = corners[1..]
+            .iter()
+            .fold((corners[0], corners[0]), |(min, max), p| {
+                (
+                    Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+                    Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            });
+        // Antialiasing reaches into the next pixel
+        let (x0, y0) = (min.x.floor() - 1.0, min.y.floor() - 1.0);
+        let (x1, y1) = (max.x.ceil() + 1.0, max.y.ceil() + 1.0);
+        self.touch_pixels(Rectangle::new(x0, y0, x1 - x0, y1 - y0));
+    }
+
+    fn touch_circle(&self, center: Vector2, r: f64) {
+        self.touch(Rectangle::new(center.x - r, center.y - r, 2.0 * r, 2.0 * r));
+    }
+
+    fn touch_all(&self) {
+        self.touch_pixels(Rectangle::new(
+            0.0,
+            0.0,
+            self.width() as f64,
+            self.height() as f64,
+        ));
+    }
+
+    fn touch_pixels(&self, rect: Rectangle) {
+        let canvas = Rectangle::new(0.0, 0.0, self.width() as f64, self.height() as f64);
+        if let Some(rect) = rect.intersection(canvas) {
+            let stale = match self.stale.get() {
+                Some(stale) => stale.union(rect),
+                None => rect,
+            };
+            self.stale.set(Some(stale));
+        }
+    }
+
+    fn flush_gl_to_2d(&self, gpu: &Gpu) {
+        gpu.present(&self.target);
+        let (w, h) = (self.width() as f64, self.height() as f64);
+        self.context_2d.save();
+        self.context_2d
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+        self.context_2d
+            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                gpu.canvas(),
+                0.0,
+                0.0,
+                w,
+                h,
+                0.0,
+                0.0,
+                w,
+                h,
+            )
+            .unwrap();
+        self.context_2d.restore();
+    }
+
+    fn replace_2d_with_gl(&self, gpu: &Gpu) {
+        self.context_2d.save();
         self.context_2d
-            .draw_image_with_html_canvas_element(&self.canvas_gl, 0.0, 0.0)
+            .set_global_composite_operation("copy")
+            .unwrap();
+        self.flush_gl_to_2d(gpu);
+        self.context_2d.restore();
+    }
+}
+
+impl Drop for VirtualContext {
+    fn drop(&mut self) {
+        let gpu = self.gpu.borrow();
+        gpu.delete_texture(&self.texture);
+        gpu.delete_target(&self.target);
+    }
+}
+
+/// Whole pixel rectangle holding every dab, `None` without dabs.
+fn dabs_extent(dabs: &[Dab]) -> Option<Rectangle> {
+    let (min, max) = dabs
+        .iter()
+        .map(|dab| {
+            let shape = dab.shape;
+            let r = shape.radii.x.max(shape.radii.y);
+            let r = Vector2::new(r, r);
+            (shape.center - r, shape.center + r)
+        })
+        .reduce(|(a_min, a_max), (b_min, b_max)| {
+            (
+                Vector2::new(a_min.x.min(b_min.x), a_min.y.min(b_min.y)),
+                Vector2::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y)),
+            )
+        })?;
+    let (x0, y0) = (min.x.floor() - 1.0, min.y.floor() - 1.0);
+    let (x1, y1) = (max.x.ceil() + 1.0, max.y.ceil() + 1.0);
+    Some(Rectangle::new(x0, y0, x1 - x0, y1 - y0))
+}
+
+fn fill_round_dab(target: &CanvasRenderingContext2d, dab: &Dab) {
+    let shape = dab.shape;
+    let r = shape.radii.x;
+    target.save();
+    target.translate(shape.center.x, shape.center.y).unwrap();
+    target.rotate(shape.angle).unwrap();
+    target.scale(1.0, shape.radii.y / r).unwrap();
+    target.begin_path();
+    if dab.hardness >= 1.0 {
+        target.set_fill_style(&JsValue::from_str(&dab.color.to_style()));
+    } else {
+        let gradient = target
+            .create_radial_gradient(0.0, 0.0, r * dab.hardness, 0.0, 0.0, r)
+            .unwrap();
+        gradient.add_color_stop(0.0, &dab.color.to_style()).unwrap();
+        gradient
+            .add_color_stop(1.0, &dab.color.with_alpha(0).to_style())
             .unwrap();
+        target.set_fill_style(&gradient);
+    }
+    target
+        .arc(0.0, 0.0, r, 0.0, std::f64::consts::PI * 2.0)
+        .unwrap();
+    target.fill();
+    target.close_path();
+    target.restore();
+}
+
+pub fn create_canvas_2d() -> (HtmlCanvasElement, CanvasRenderingContext2d) {
+    let canvas: HtmlCanvasElement = document()
+        .create_element("canvas")
+        .unwrap()
+        .unchecked_into();
+    let context = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()
+        .unwrap();
+    (canvas, context)
+}
+
+pub fn canvas_from_image(image: &RgbaImage) -> HtmlCanvasElement {
+    let (canvas, context) = create_canvas_2d();
+    canvas.set_width(image.width);
+    canvas.set_height(image.height);
+    // ImageData can't be empty
+    if image.width == 0 || image.height == 0 {
+        return canvas;
     }
+    let data = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&image.data),
+        image.width,
+        image.height,
+    )
+    .unwrap();
+    context.put_image_data(&data, 0.0, 0.0).unwrap();
+    canvas
+}
+
+pub fn image_from_element(image: &HtmlImageElement) -> RgbaImage {
+    let (canvas, context) = create_canvas_2d();
+    canvas.set_width(image.natural_width());
+    canvas.set_height(image.natural_height());
+    context
+        .draw_image_with_html_image_element(image, 0.0, 0.0)
+        .unwrap();
+    let data = context
+        .get_image_data(0.0, 0.0, canvas.width() as f64, canvas.height() as f64)
+        .unwrap();
+    RgbaImage::from_data(data.width(), data.height(), data.data().0).unwrap()
 }